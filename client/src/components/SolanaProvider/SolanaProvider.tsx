@@ -10,6 +10,7 @@ import { EventName } from "../../constants";
 import { connAtom } from "../../state";
 import { PgConnection } from "../../utils/pg";
 import { usePlaynet } from "./usePlaynet";
+import { useExternalWallet } from "./useExternalWallet";
 
 /**
  * Connection and Wallet provider
@@ -40,9 +41,19 @@ const SolanaProvider: FC = ({ children }) => {
         fetch: customFetch.fetch,
       }}
     >
-      <WalletProvider wallets={wallets}>{children}</WalletProvider>
+      <WalletProvider wallets={wallets}>
+        <ExternalWalletSync />
+        {children}
+      </WalletProvider>
     </ConnectionProvider>
   );
 };
 
+/** Needs to be inside `WalletProvider` to access the connected wallet */
+const ExternalWalletSync = () => {
+  useExternalWallet();
+
+  return null;
+};
+
 export default SolanaProvider;
@@ -0,0 +1,20 @@
+import { useEffect } from "react";
+import { useWallet } from "@solana/wallet-adapter-react";
+
+import { PgWallet } from "../../utils/pg";
+
+/**
+ * Keep the external wallet of `PgWallet` in sync with the wallet adapter so
+ * that WASM can sign with the connected browser wallet.
+ */
+export const useExternalWallet = () => {
+  const { publicKey, signMessage, signTransaction } = useWallet();
+
+  useEffect(() => {
+    if (publicKey && signMessage && signTransaction) {
+      PgWallet.setExternalWallet({ publicKey, signMessage, signTransaction });
+    } else {
+      PgWallet.setExternalWallet(null);
+    }
+  }, [publicKey, signMessage, signTransaction]);
+};
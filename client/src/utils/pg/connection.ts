@@ -8,6 +8,18 @@ export interface PgConnectionConfig {
   endpoint: Endpoint;
   commitment: Commitment;
   preflightChecks: boolean;
+  /** Amount of times to retry failed RPC requests from WASM */
+  retryCount?: number;
+  /** Custom headers to send with every RPC request, e.g. API keys */
+  headers?: { [name: string]: string };
+  /** Compute unit price in micro-lamports to use as the priority fee */
+  priorityFee?: number;
+  /**
+   * Commitment to confirm transactions with from WASM.
+   *
+   * NOTE: `commitment` is used when this is not set.
+   */
+  confirmCommitment?: Commitment;
 }
 
 export class PgConnection {
@@ -16,6 +28,8 @@ export class PgConnection {
     endpoint: Endpoint.DEVNET,
     commitment: "confirmed",
     preflightChecks: true,
+    retryCount: 3,
+    priorityFee: 0,
   };
 
   /** Get the endpoint from localStorage */
@@ -33,6 +47,39 @@ export class PgConnection {
     return this.getConnectionConfig().preflightChecks;
   }
 
+  /** Get the retry count from localStorage */
+  static get retryCount(): number {
+    return (
+      this.getConnectionConfig().retryCount ??
+      this._DEFAULT_CONNECTION.retryCount!
+    );
+  }
+
+  /**
+   * Get the priority fee from localStorage.
+   *
+   * NOTE: `bigint` is used because it's `u64` in WASM.
+   */
+  static get priorityFee(): bigint {
+    return BigInt(
+      this.getConnectionConfig().priorityFee ??
+        this._DEFAULT_CONNECTION.priorityFee!
+    );
+  }
+
+  /** Get the confirmation commitment from localStorage */
+  static get confirmCommitment(): Commitment | null {
+    return this.getConnectionConfig().confirmCommitment ?? null;
+  }
+
+  /** Get the JSON.stringified custom headers from localStorage */
+  static get headersStr() {
+    const headers = this.getConnectionConfig().headers;
+    if (!headers) return null;
+
+    return JSON.stringify(headers);
+  }
+
   /**
    * Get the connection config from localStorage or create the default connection
    * if it doesn't exist
@@ -58,7 +105,15 @@ export class PgConnection {
    * @param params update config values
    */
   static update(params: Partial<PgConnectionConfig>) {
-    const { endpoint, commitment, preflightChecks } = params;
+    const {
+      endpoint,
+      commitment,
+      preflightChecks,
+      retryCount,
+      headers,
+      priorityFee,
+      confirmCommitment,
+    } = params;
     const conn = this.getConnectionConfig();
 
     if (endpoint) conn.endpoint = endpoint;
@@ -66,6 +121,12 @@ export class PgConnection {
     if (preflightChecks !== undefined) {
       conn.preflightChecks = !!preflightChecks;
     }
+    if (retryCount !== undefined) conn.retryCount = retryCount;
+    if (headers !== undefined) conn.headers = headers;
+    if (priorityFee !== undefined) conn.priorityFee = priorityFee;
+    if (confirmCommitment !== undefined) {
+      conn.confirmCommitment = confirmCommitment;
+    }
 
     localStorage.setItem(this._CONNECTION_KEY, JSON.stringify(conn));
   }
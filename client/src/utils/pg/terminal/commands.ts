@@ -54,7 +54,15 @@ export class PgCommand {
               log: this._isPkgLoadingInitial(PkgName.ANCHOR_CLI),
             });
 
-            await runAnchor!(input);
+            const err = await runAnchor!(input);
+            if (err) {
+              if (err.code === "NOT_DEPLOYED") {
+                PgTerminal.logWasm(
+                  PgTerminal.info("Deploy the program before running this command.")
+                );
+              }
+              err.free();
+            }
           });
         },
         preCheck: PgWallet.checkIsPgConnected,
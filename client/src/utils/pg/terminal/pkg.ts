@@ -2,6 +2,7 @@ import { GITHUB_URL } from "../../../constants";
 import { PgTerminal } from "./terminal";
 
 export interface Pkgs {
+  initPanicHook?: typeof import("@solana-playground/anchor-cli-wasm").initPanicHook;
   Playnet?: typeof import("@solana-playground/playnet").Playnet;
   compileSeahorse?: typeof import("@solana-playground/seahorse-compile-wasm").compileSeahorse;
   runAnchor?: typeof import("@solana-playground/anchor-cli-wasm").runAnchor;
@@ -111,8 +112,11 @@ export class PgPkg {
    */
   private static async _loadPkg(pkgName: PkgName) {
     switch (pkgName) {
-      case PkgName.ANCHOR_CLI:
-        return await import("@solana-playground/anchor-cli-wasm");
+      case PkgName.ANCHOR_CLI: {
+        const pkg = await import("@solana-playground/anchor-cli-wasm");
+        pkg.initPanicHook();
+        return pkg;
+      }
       case PkgName.PLAYNET:
         return await import("@solana-playground/playnet");
       case PkgName.RUSTFMT:
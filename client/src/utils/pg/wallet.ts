@@ -1,4 +1,4 @@
-import { Keypair, PublicKey, Transaction } from "@solana/web3.js";
+import { Keypair, Message, PublicKey, Transaction } from "@solana/web3.js";
 import { AnchorWallet } from "@solana/wallet-adapter-react";
 import * as ed25519 from "@noble/ed25519";
 
@@ -21,6 +21,21 @@ interface LsWallet {
    * causing problems while saving to localStorage.
    */
   sk: Array<number>;
+  /**
+   * Additional named wallets, keyed by their label.
+   *
+   * NOTE: The main wallet (`sk`) is always available under the default name.
+   */
+  wallets?: { [name: string]: Array<number> };
+  /** Name of the currently selected wallet */
+  currentName?: string;
+}
+
+/** Browser wallet that is connected via wallet adapter */
+interface ExternalWallet {
+  publicKey: PublicKey;
+  signMessage: (message: Uint8Array) => Promise<Uint8Array>;
+  signTransaction: (tx: Transaction) => Promise<Transaction>;
 }
 
 /**
@@ -111,6 +126,80 @@ export class PgWallet implements AnchorWallet {
     return Uint8Array.from(PgWallet.getKp().secretKey);
   }
 
+  /** Name of the currently selected wallet */
+  static get currentName() {
+    return PgWallet.getLs()?.currentName ?? PgWallet._DEFAULT_WALLET_NAME;
+  }
+
+  /**
+   * Get the keypair bytes of a wallet by its name
+   *
+   * @param name label of the wallet
+   * @returns the keypair bytes if the wallet exists, otherwise `null`
+   */
+  static keypairBytesFor(name: string) {
+    if (name === PgWallet._DEFAULT_WALLET_NAME) return PgWallet.keypairBytes;
+
+    const sk = PgWallet.getLs()?.wallets?.[name];
+    if (!sk) return null;
+
+    return Uint8Array.from(sk);
+  }
+
+  /** Public key of the connected external wallet, `null` if not connected */
+  static get externalPkStr() {
+    return PgWallet._externalWallet?.publicKey.toBase58() ?? null;
+  }
+
+  /**
+   * Set the connected external wallet(e.g. Phantom, Backpack), `null` to
+   * disconnect
+   */
+  static setExternalWallet(wallet: ExternalWallet | null) {
+    PgWallet._externalWallet = wallet;
+  }
+
+  /**
+   * Sign a message with the connected external wallet
+   *
+   * @param message message to sign
+   * @returns the signature bytes
+   */
+  static async externalSign(message: Uint8Array) {
+    return await PgWallet._getExternalWallet().signMessage(message);
+  }
+
+  /**
+   * Sign a serialized legacy transaction message with the connected external
+   * wallet.
+   *
+   * NOTE: Wallets refuse to sign transaction messages with `signMessage`.
+   *
+   * @param messageBytes serialized transaction message
+   * @returns the signature bytes of the external wallet
+   */
+  static async externalSignTransaction(messageBytes: Uint8Array) {
+    const wallet = PgWallet._getExternalWallet();
+
+    // Versioned messages have the highest bit of the first byte set
+    if (messageBytes[0] & 0x80) {
+      throw new Error(
+        "Versioned transactions are not supported with the external wallet"
+      );
+    }
+
+    const tx = Transaction.populate(Message.from(messageBytes));
+    const signedTx = await wallet.signTransaction(tx);
+    const signature = signedTx.signatures.find(({ publicKey }) =>
+      publicKey.equals(wallet.publicKey)
+    )?.signature;
+    if (!signature) {
+      throw new Error("External wallet didn't sign the transaction");
+    }
+
+    return Uint8Array.from(signature);
+  }
+
   /**
    * @returns wallet info from localStorage
    */
@@ -133,6 +222,9 @@ export class PgWallet implements AnchorWallet {
     if (updateParams.connected !== undefined)
       lsWallet.connected = updateParams.connected;
     if (updateParams.sk) lsWallet.sk = updateParams.sk;
+    if (updateParams.wallets) lsWallet.wallets = updateParams.wallets;
+    if (updateParams.currentName !== undefined)
+      lsWallet.currentName = updateParams.currentName;
 
     localStorage.setItem(PgWallet._WALLET_KEY, JSON.stringify(lsWallet));
   }
@@ -187,9 +279,24 @@ export class PgWallet implements AnchorWallet {
     );
   }
 
+  /** Currently connected external wallet */
+  private static _externalWallet: ExternalWallet | null = null;
+
+  /** Get the connected external wallet or throw if it's not connected */
+  private static _getExternalWallet() {
+    if (!PgWallet._externalWallet) {
+      throw new Error("External wallet is not connected");
+    }
+
+    return PgWallet._externalWallet;
+  }
+
   /** localStorage key for the wallet */
   private static readonly _WALLET_KEY = "wallet";
 
+  /** Name of the main wallet */
+  private static readonly _DEFAULT_WALLET_NAME = "default";
+
   /** Randomly generated default localStorage wallet */
   private static readonly _DEFAULT_LS_WALLET: LsWallet = {
     setupCompleted: false,
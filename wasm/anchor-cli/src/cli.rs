@@ -1,8 +1,15 @@
+use std::str::FromStr;
+
 use clap::Parser;
 use solana_playground_utils_wasm::js::PgTerminal;
+use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::keypair::Keypair};
 use wasm_bindgen::prelude::*;
 
-use crate::commands::idl::{process_idl, IdlCommand};
+use crate::{
+    commands::idl::{process_idl, IdlCommand},
+    error::error_code,
+    utils,
+};
 
 #[derive(Parser)]
 #[clap(name = "sugar-cli", version, about)]
@@ -16,19 +23,87 @@ enum Cli {
 
 pub type CliResult<T = ()> = anyhow::Result<T>;
 
+/// Print readable panic messages to the browser console instead of `RuntimeError: unreachable`.
+///
+/// Should be called once after the package is loaded, calling it again is a no-op.
+#[wasm_bindgen(js_name = initPanicHook)]
+pub fn init_panic_hook() {
+    console_error_panic_hook::set_once();
+}
+
+/// Sign the message with the given keypair bytes, returns the 64 byte signature
+#[wasm_bindgen(js_name = signMessage)]
+pub fn sign_message(keypair_bytes: &[u8], message: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let keypair = Keypair::from_bytes(keypair_bytes)
+        .map_err(|e| JsValue::from_str(&format!("Invalid keypair: {e}")))?;
+    Ok(utils::sign_message(&keypair, message).as_ref().to_vec())
+}
+
+/// Verify the signature of the message, returns `false` if the inputs are invalid
+#[wasm_bindgen(js_name = verifySignature)]
+pub fn verify_signature(pubkey: &str, message: &[u8], signature: &[u8]) -> bool {
+    match (Pubkey::from_str(pubkey), Signature::try_from(signature)) {
+        (Ok(pubkey), Ok(signature)) => utils::verify_signature(&pubkey, message, &signature),
+        _ => false,
+    }
+}
+
+/// Error of a failed command
+#[wasm_bindgen]
+pub struct CommandError {
+    code: Option<&'static str>,
+    message: String,
+}
+
+#[wasm_bindgen]
+impl CommandError {
+    /// Stable machine readable code of the error, `undefined` if the error doesn't have a code
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> Option<String> {
+        self.code.map(Into::into)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(e: anyhow::Error) -> Self {
+        Self {
+            code: error_code(&e),
+            message: e.to_string(),
+        }
+    }
+}
+
+/// Run the Anchor command, returns the error if the command fails
 #[wasm_bindgen(js_name = runAnchor)]
-pub async fn run_anchor(cmd: &str) {
-    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+pub async fn run_anchor(cmd: &str) -> Option<CommandError> {
+    init_panic_hook();
 
     let args = cmd.split_ascii_whitespace().collect::<Vec<&str>>();
 
     match Cli::try_parse_from(args) {
         Ok(cli) => match cli {
             Cli::Idl { subcmd } => match process_idl(subcmd).await {
-                Err(e) => PgTerminal::log_wasm(&format!("Process error: {}", e.to_string())),
-                _ => (),
+                Err(e) => {
+                    let err = CommandError::from(e);
+                    match err.code {
+                        Some(code) => {
+                            PgTerminal::log_wasm(&format!("Process error[{code}]: {}", err.message))
+                        }
+                        None => PgTerminal::log_wasm(&format!("Process error: {}", err.message)),
+                    }
+                    Some(err)
+                }
+                _ => None,
             },
         },
-        Err(e) => PgTerminal::log_wasm(&e.to_string()),
+        Err(e) => {
+            PgTerminal::log_wasm(&e.to_string());
+            None
+        }
     }
 }
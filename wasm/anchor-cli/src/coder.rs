@@ -0,0 +1,544 @@
+//! Borsh <-> JSON conversion driven by the IDL type definitions.
+//!
+//! JSON conventions:
+//! - Integers up to 32 bits are numbers, 64 and 128 bit integers are strings. Both numbers and
+//!   strings are accepted when encoding.
+//! - Public keys are base58 strings
+//! - Bytes are arrays of numbers
+//! - Options are `null` or the inner value
+//! - Enums are `{ "VariantName": <fields> }`
+
+use std::{fmt::Display, str::FromStr};
+
+use anchor_syn::idl::{EnumFields, Idl, IdlField, IdlType, IdlTypeDefinitionTy};
+use anyhow::anyhow;
+use serde_json::{Map, Value};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{cli::CliResult, error::PgError};
+
+/// Decode the given fields into a JSON object
+pub fn decode_fields(idl: &Idl, fields: &[IdlField], data: &mut &[u8]) -> CliResult<Value> {
+    let mut map = Map::new();
+    for field in fields {
+        map.insert(field.name.clone(), decode_type(idl, &field.ty, data)?);
+    }
+
+    Ok(Value::Object(map))
+}
+
+/// Decode a user defined type
+pub fn decode_type_definition(
+    idl: &Idl,
+    ty: &IdlTypeDefinitionTy,
+    data: &mut &[u8],
+) -> CliResult<Value> {
+    match ty {
+        IdlTypeDefinitionTy::Struct { fields } => decode_fields(idl, fields, data),
+        IdlTypeDefinitionTy::Enum { variants } => {
+            let index = take(data, 1)?[0] as usize;
+            let variant = variants
+                .get(index)
+                .ok_or_else(|| anyhow!("Invalid enum variant index: {index}"))?;
+
+            let value = match &variant.fields {
+                Some(EnumFields::Named(fields)) => decode_fields(idl, fields, data)?,
+                Some(EnumFields::Tuple(types)) => Value::Array(
+                    types
+                        .iter()
+                        .map(|ty| decode_type(idl, ty, data))
+                        .collect::<CliResult<_>>()?,
+                ),
+                None => Value::Object(Map::new()),
+            };
+
+            let mut map = Map::new();
+            map.insert(variant.name.clone(), value);
+            Ok(Value::Object(map))
+        }
+    }
+}
+
+/// Decode a single value of the given type
+pub fn decode_type(idl: &Idl, ty: &IdlType, data: &mut &[u8]) -> CliResult<Value> {
+    Ok(match ty {
+        IdlType::Bool => Value::Bool(take(data, 1)?[0] != 0),
+        IdlType::U8 => take(data, 1)?[0].into(),
+        IdlType::I8 => (take(data, 1)?[0] as i8).into(),
+        IdlType::U16 => u16::from_le_bytes(take_array(data)?).into(),
+        IdlType::I16 => i16::from_le_bytes(take_array(data)?).into(),
+        IdlType::U32 => u32::from_le_bytes(take_array(data)?).into(),
+        IdlType::I32 => i32::from_le_bytes(take_array(data)?).into(),
+        IdlType::F32 => f32::from_le_bytes(take_array(data)?).into(),
+        IdlType::U64 => u64::from_le_bytes(take_array(data)?).to_string().into(),
+        IdlType::I64 => i64::from_le_bytes(take_array(data)?).to_string().into(),
+        IdlType::F64 => f64::from_le_bytes(take_array(data)?).into(),
+        IdlType::U128 => u128::from_le_bytes(take_array(data)?).to_string().into(),
+        IdlType::I128 => i128::from_le_bytes(take_array(data)?).to_string().into(),
+        IdlType::Bytes => {
+            let len = u32::from_le_bytes(take_array(data)?) as usize;
+            take(data, len)?.to_vec().into()
+        }
+        IdlType::String => {
+            let len = u32::from_le_bytes(take_array(data)?) as usize;
+            String::from_utf8(take(data, len)?.to_vec())?.into()
+        }
+        IdlType::PublicKey => Pubkey::new_from_array(take_array(data)?).to_string().into(),
+        IdlType::Defined(name) => {
+            let ty_def = find_type_definition(idl, name)?;
+            decode_type_definition(idl, ty_def, data)?
+        }
+        IdlType::Option(inner) => match take(data, 1)?[0] {
+            0 => Value::Null,
+            _ => decode_type(idl, inner, data)?,
+        },
+        IdlType::Vec(inner) => {
+            let len = u32::from_le_bytes(take_array(data)?) as usize;
+            Value::Array(
+                (0..len)
+                    .map(|_| decode_type(idl, inner, data))
+                    .collect::<CliResult<_>>()?,
+            )
+        }
+        IdlType::Array(inner, len) => Value::Array(
+            (0..*len)
+                .map(|_| decode_type(idl, inner, data))
+                .collect::<CliResult<_>>()?,
+        ),
+        #[allow(unreachable_patterns)]
+        _ => return Err(anyhow!("Unsupported IDL type: {ty:?}")),
+    })
+}
+
+/// Encode the fields of the given JSON object
+pub fn encode_fields(
+    idl: &Idl,
+    fields: &[IdlField],
+    value: &Value,
+    buf: &mut Vec<u8>,
+) -> CliResult {
+    let map = value
+        .as_object()
+        .ok_or_else(|| anyhow!("Expected an object, found: {value}"))?;
+    if let Some(unknown) = map
+        .keys()
+        .find(|key| fields.iter().all(|field| field.name != **key))
+    {
+        return Err(anyhow!("Unknown field '{unknown}'"));
+    }
+
+    for field in fields {
+        let value = match (map.get(&field.name), &field.ty) {
+            (Some(value), _) => value,
+            // Missing optional fields are allowed
+            (None, IdlType::Option(_)) => &Value::Null,
+            (None, _) => return Err(anyhow!("Missing field '{}'", field.name)),
+        };
+        encode_type(idl, &field.ty, value, buf)
+            .map_err(|e| anyhow!("Invalid field '{}': {e}", field.name))?;
+    }
+
+    Ok(())
+}
+
+/// Encode a user defined type
+pub fn encode_type_definition(
+    idl: &Idl,
+    ty: &IdlTypeDefinitionTy,
+    value: &Value,
+    buf: &mut Vec<u8>,
+) -> CliResult {
+    match ty {
+        IdlTypeDefinitionTy::Struct { fields } => encode_fields(idl, fields, value, buf),
+        IdlTypeDefinitionTy::Enum { variants } => {
+            // Unit variants can also be given as strings
+            let (name, fields_value) = match value {
+                Value::String(name) => (name, &Value::Null),
+                Value::Object(map) if map.len() == 1 => map.iter().next().unwrap(),
+                _ => return Err(anyhow!("Expected an enum variant, found: {value}")),
+            };
+            let (index, variant) = variants
+                .iter()
+                .enumerate()
+                .find(|(_, variant)| variant.name == *name)
+                .ok_or_else(|| anyhow!("Unknown enum variant '{name}'"))?;
+            buf.push(index as u8);
+
+            match &variant.fields {
+                Some(EnumFields::Named(fields)) => encode_fields(idl, fields, fields_value, buf),
+                Some(EnumFields::Tuple(types)) => {
+                    let values = fields_value
+                        .as_array()
+                        .filter(|values| values.len() == types.len())
+                        .ok_or_else(|| {
+                            anyhow!("Expected {} tuple fields for '{name}'", types.len())
+                        })?;
+                    for (ty, value) in types.iter().zip(values) {
+                        encode_type(idl, ty, value, buf)?;
+                    }
+                    Ok(())
+                }
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Encode a single value of the given type
+pub fn encode_type(idl: &Idl, ty: &IdlType, value: &Value, buf: &mut Vec<u8>) -> CliResult {
+    match ty {
+        IdlType::Bool => buf.push(
+            value
+                .as_bool()
+                .ok_or_else(|| anyhow!("Expected a bool, found: {value}"))? as u8,
+        ),
+        IdlType::U8 => buf.push(parse_integer::<u8>(value)?),
+        IdlType::I8 => buf.extend(parse_integer::<i8>(value)?.to_le_bytes()),
+        IdlType::U16 => buf.extend(parse_integer::<u16>(value)?.to_le_bytes()),
+        IdlType::I16 => buf.extend(parse_integer::<i16>(value)?.to_le_bytes()),
+        IdlType::U32 => buf.extend(parse_integer::<u32>(value)?.to_le_bytes()),
+        IdlType::I32 => buf.extend(parse_integer::<i32>(value)?.to_le_bytes()),
+        IdlType::F32 => buf.extend((parse_float(value)? as f32).to_le_bytes()),
+        IdlType::U64 => buf.extend(parse_integer::<u64>(value)?.to_le_bytes()),
+        IdlType::I64 => buf.extend(parse_integer::<i64>(value)?.to_le_bytes()),
+        IdlType::F64 => buf.extend(parse_float(value)?.to_le_bytes()),
+        IdlType::U128 => buf.extend(parse_integer::<u128>(value)?.to_le_bytes()),
+        IdlType::I128 => buf.extend(parse_integer::<i128>(value)?.to_le_bytes()),
+        IdlType::Bytes => {
+            let bytes = value
+                .as_array()
+                .ok_or_else(|| anyhow!("Expected bytes, found: {value}"))?
+                .iter()
+                .map(parse_integer::<u8>)
+                .collect::<CliResult<Vec<_>>>()?;
+            buf.extend((bytes.len() as u32).to_le_bytes());
+            buf.extend(bytes);
+        }
+        IdlType::String => {
+            let s = value
+                .as_str()
+                .ok_or_else(|| anyhow!("Expected a string, found: {value}"))?;
+            buf.extend((s.len() as u32).to_le_bytes());
+            buf.extend(s.as_bytes());
+        }
+        IdlType::PublicKey => {
+            let s = value
+                .as_str()
+                .ok_or_else(|| anyhow!("Expected a public key, found: {value}"))?;
+            let pubkey = Pubkey::from_str(s).map_err(|e| PgError::InvalidPubkey {
+                value: s.to_owned(),
+                reason: e.to_string(),
+            })?;
+            buf.extend(pubkey.to_bytes());
+        }
+        IdlType::Defined(name) => {
+            let ty_def = find_type_definition(idl, name)?;
+            encode_type_definition(idl, ty_def, value, buf)?;
+        }
+        IdlType::Option(inner) => match value {
+            Value::Null => buf.push(0),
+            _ => {
+                buf.push(1);
+                encode_type(idl, inner, value, buf)?;
+            }
+        },
+        IdlType::Vec(inner) => {
+            let values = value
+                .as_array()
+                .ok_or_else(|| anyhow!("Expected an array, found: {value}"))?;
+            buf.extend((values.len() as u32).to_le_bytes());
+            for value in values {
+                encode_type(idl, inner, value, buf)?;
+            }
+        }
+        IdlType::Array(inner, len) => {
+            let values = value
+                .as_array()
+                .filter(|values| values.len() == *len)
+                .ok_or_else(|| anyhow!("Expected an array of length {len}, found: {value}"))?;
+            for value in values {
+                encode_type(idl, inner, value, buf)?;
+            }
+        }
+        #[allow(unreachable_patterns)]
+        _ => return Err(anyhow!("Unsupported IDL type: {ty:?}")),
+    }
+
+    Ok(())
+}
+
+/// Parse an integer from either a JSON number or a string
+fn parse_integer<T>(value: &Value) -> CliResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let s = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.to_owned(),
+        _ => return Err(anyhow!("Expected an integer, found: {value}")),
+    };
+
+    s.parse().map_err(|e| anyhow!("Invalid integer '{s}': {e}"))
+}
+
+/// Parse a float from either a JSON number or a string
+fn parse_float(value: &Value) -> CliResult<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow!("Expected a float, found: {value}"))
+}
+
+/// Find a user defined type by its name in either the types or the accounts of the IDL
+fn find_type_definition<'a>(idl: &'a Idl, name: &str) -> CliResult<&'a IdlTypeDefinitionTy> {
+    idl.types
+        .iter()
+        .chain(idl.accounts.iter())
+        .find(|ty_def| ty_def.name == name)
+        .map(|ty_def| &ty_def.ty)
+        .ok_or_else(|| anyhow!("Type '{name}' not found in the IDL"))
+}
+
+/// Take the next `len` bytes from the data
+fn take<'a>(data: &mut &'a [u8], len: usize) -> CliResult<&'a [u8]> {
+    if data.len() < len {
+        return Err(anyhow!("Unexpected end of data"));
+    }
+
+    let (taken, rest) = data.split_at(len);
+    *data = rest;
+    Ok(taken)
+}
+
+/// Take the next `N` bytes from the data as an array
+fn take_array<const N: usize>(data: &mut &[u8]) -> CliResult<[u8; N]> {
+    Ok(take(data, N)?.try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn test_idl() -> Idl {
+        serde_json::from_value(json!({
+            "version": "0.1.0",
+            "name": "test",
+            "instructions": [],
+            "types": [
+                {
+                    "name": "Choice",
+                    "type": {
+                        "kind": "enum",
+                        "variants": [
+                            { "name": "Unit" },
+                            { "name": "Named", "fields": [{ "name": "a", "type": "u8" }] },
+                            { "name": "Tuple", "fields": ["u64", "bool"] }
+                        ]
+                    }
+                },
+                {
+                    "name": "Point",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "x", "type": "i16" },
+                            { "name": "y", "type": "i16" }
+                        ]
+                    }
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn fields(fields: Value) -> Vec<IdlField> {
+        serde_json::from_value(fields).unwrap()
+    }
+
+    fn ty(ty: Value) -> IdlType {
+        serde_json::from_value(ty).unwrap()
+    }
+
+    fn encode(ty: &IdlType, value: Value) -> CliResult<Vec<u8>> {
+        let mut buf = vec![];
+        encode_type(&test_idl(), ty, &value, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn decode(ty: &IdlType, mut data: &[u8]) -> CliResult<Value> {
+        let value = decode_type(&test_idl(), ty, &mut data)?;
+        assert!(data.is_empty(), "{} bytes left", data.len());
+        Ok(value)
+    }
+
+    #[test]
+    fn test_round_trip_fields() {
+        let idl = test_idl();
+        let fields = fields(json!([
+            { "name": "flag", "type": "bool" },
+            { "name": "small", "type": "u8" },
+            { "name": "amount", "type": "u64" },
+            { "name": "delta", "type": "i64" },
+            { "name": "big", "type": "u128" },
+            { "name": "owner", "type": "publicKey" },
+            { "name": "label", "type": "string" },
+            { "name": "raw", "type": "bytes" },
+            { "name": "list", "type": { "vec": "u16" } },
+            { "name": "pair", "type": { "array": ["u8", 2] } },
+            { "name": "maybe", "type": { "option": "u32" } },
+            { "name": "point", "type": { "defined": "Point" } }
+        ]));
+        let owner = Pubkey::new_unique().to_string();
+        let value = json!({
+            "flag": true,
+            "small": 7,
+            "amount": 5,
+            "delta": "-5",
+            "big": u128::MAX.to_string(),
+            "owner": owner,
+            "label": "hi",
+            "raw": [1, 2],
+            "list": [3, 4],
+            "pair": [5, 6],
+            "maybe": 8,
+            "point": { "x": -1, "y": 2 }
+        });
+
+        let mut buf = vec![];
+        encode_fields(&idl, &fields, &value, &mut buf).unwrap();
+        assert_eq!(&buf[..2], &[1, 7]);
+        assert_eq!(&buf[2..10], &5u64.to_le_bytes());
+
+        let mut data = &buf[..];
+        let decoded = decode_fields(&idl, &fields, &mut data).unwrap();
+        assert!(data.is_empty());
+
+        // 64 and 128 bit integers are decoded as strings
+        let mut expected = value;
+        expected["amount"] = json!("5");
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn test_missing_fields() {
+        let idl = test_idl();
+        let fields = fields(json!([
+            { "name": "maybe", "type": { "option": "u8" } },
+            { "name": "required", "type": "u8" }
+        ]));
+
+        let mut buf = vec![];
+        encode_fields(&idl, &fields, &json!({ "required": 1 }), &mut buf).unwrap();
+        assert_eq!(buf, vec![0, 1]);
+        assert_eq!(
+            decode_fields(&idl, &fields, &mut &buf[..]).unwrap(),
+            json!({ "maybe": null, "required": 1 })
+        );
+
+        let err = encode_fields(&idl, &fields, &json!({ "maybe": 1 }), &mut vec![]).unwrap_err();
+        assert_eq!(err.to_string(), "Missing field 'required'");
+        let err = encode_fields(
+            &idl,
+            &fields,
+            &json!({ "required": 1, "extra": 2 }),
+            &mut vec![],
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Unknown field 'extra'");
+    }
+
+    #[test]
+    fn test_enum() {
+        let choice = ty(json!({ "defined": "Choice" }));
+
+        // Unit variants can be given as strings
+        assert_eq!(encode(&choice, json!("Unit")).unwrap(), vec![0]);
+        assert_eq!(encode(&choice, json!({ "Unit": {} })).unwrap(), vec![0]);
+        assert_eq!(decode(&choice, &[0]).unwrap(), json!({ "Unit": {} }));
+
+        let named = json!({ "Named": { "a": 3 } });
+        assert_eq!(encode(&choice, named.clone()).unwrap(), vec![1, 3]);
+        assert_eq!(decode(&choice, &[1, 3]).unwrap(), named);
+
+        let tuple = json!({ "Tuple": ["9", true] });
+        let buf = encode(&choice, tuple.clone()).unwrap();
+        assert_eq!(buf[0], 2);
+        assert_eq!(&buf[1..9], &9u64.to_le_bytes());
+        assert_eq!(buf[9], 1);
+        assert_eq!(decode(&choice, &buf).unwrap(), tuple);
+
+        let err = encode(&choice, json!({ "Tuple": ["9"] })).unwrap_err();
+        assert_eq!(err.to_string(), "Expected 2 tuple fields for 'Tuple'");
+        let err = encode(&choice, json!("Other")).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown enum variant 'Other'");
+        let err = decode(&choice, &[3]).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid enum variant index: 3");
+
+        let err = encode(&ty(json!({ "defined": "Missing" })), json!({})).unwrap_err();
+        assert_eq!(err.to_string(), "Type 'Missing' not found in the IDL");
+    }
+
+    #[test]
+    fn test_array_length_mismatch() {
+        let array = ty(json!({ "array": ["u8", 2] }));
+        assert!(encode(&array, json!([1, 2])).is_ok());
+
+        let err = encode(&array, json!([1])).unwrap_err();
+        assert_eq!(err.to_string(), "Expected an array of length 2, found: [1]");
+        assert!(encode(&array, json!([1, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn test_integers() {
+        let u64_ty = ty(json!("u64"));
+        assert_eq!(
+            encode(&u64_ty, json!("18446744073709551615")).unwrap(),
+            u64::MAX.to_le_bytes()
+        );
+        assert_eq!(
+            decode(&u64_ty, &u64::MAX.to_le_bytes()).unwrap(),
+            json!("18446744073709551615")
+        );
+        assert!(encode(&u64_ty, json!("-1")).is_err());
+        assert!(encode(&u64_ty, json!(true)).is_err());
+
+        let i128_ty = ty(json!("i128"));
+        let min = json!(i128::MIN.to_string());
+        let buf = encode(&i128_ty, min.clone()).unwrap();
+        assert_eq!(buf, i128::MIN.to_le_bytes());
+        assert_eq!(decode(&i128_ty, &buf).unwrap(), min);
+
+        // Out of range
+        assert!(encode(&ty(json!("u8")), json!(256)).is_err());
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        let err = decode(&ty(json!("u64")), &[0; 4]).unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected end of data");
+
+        // String length prefix is larger than the remaining data
+        let mut data = 10u32.to_le_bytes().to_vec();
+        data.extend(b"hi");
+        assert!(decode(&ty(json!("string")), &data).is_err());
+
+        assert!(decode(&ty(json!({ "vec": "u32" })), &[1, 0, 0, 0, 1]).is_err());
+        assert!(decode(&ty(json!({ "defined": "Choice" })), &[]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_pubkey() {
+        let pubkey_ty = ty(json!("publicKey"));
+        let err = encode(&pubkey_ty, json!("notbase58!!!")).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(PgError::InvalidPubkey { value, .. }) if value == "notbase58!!!"
+        ));
+        assert!(encode(&pubkey_ty, json!(1)).is_err());
+    }
+}
@@ -1,23 +1,22 @@
-use std::io::{Read, Write};
-
 use anchor_lang::{
     idl::{IdlAccount, IdlInstruction, ERASED_AUTHORITY},
     prelude::AccountMeta,
-    AccountDeserialize, AnchorDeserialize, AnchorSerialize,
+    AccountDeserialize,
 };
-use anchor_syn::idl::Idl;
-use anyhow::anyhow;
 use clap::Parser;
-use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
 use solana_playground_utils_wasm::js::PgTerminal;
 use solana_sdk::{
-    instruction::Instruction, pubkey::Pubkey, signer::keypair::Keypair, signer::Signer,
-    system_program, sysvar, transaction::Transaction,
+    instruction::Instruction, pubkey::Pubkey, signer::keypair::Keypair, signer::Signer, sysvar,
 };
 
 use crate::{
     cli::CliResult,
-    utils::{get_client, get_idl, get_keypair, get_program_id},
+    signer,
+    utils::{
+        ensure_program_deployed, fetch_idl, get_client, get_idl, get_keypair, get_program_id,
+        log_simulation_result, publish_idl, send_and_confirm, serialize_idl, serialize_idl_ix,
+        simulate_instructions, write_idl,
+    },
 };
 
 #[derive(Parser)]
@@ -28,13 +27,21 @@ pub enum IdlCommand {
         program_id: Option<Pubkey>,
     },
     /// Close the IDL account.
-    Close { program_id: Option<Pubkey> },
+    Close {
+        program_id: Option<Pubkey>,
+        /// Only simulate the transaction and print the program logs.
+        #[clap(long)]
+        simulate: bool,
+    },
     /// Command to remove the ability to modify the IDL account. This should
     /// likely be used in conjection with eliminating an "upgrade authority" on
     /// the program.
     EraseAuthority {
         #[clap(short, long)]
         program_id: Option<Pubkey>,
+        /// Only simulate the transaction and print the program logs.
+        #[clap(long)]
+        simulate: bool,
     },
     /// Fetches an IDL for the given address from a cluster.
     /// The address can be a program, IDL account, or IDL buffer.
@@ -52,6 +59,9 @@ pub enum IdlCommand {
         /// New authority of the IDL account.
         #[clap(short, long)]
         new_authority: Pubkey,
+        /// Only simulate the transaction and print the program logs.
+        #[clap(long)]
+        simulate: bool,
     },
     /// Sets a new IDL buffer for the program.
     SetBuffer {
@@ -59,6 +69,9 @@ pub enum IdlCommand {
         /// Address of the buffer account to set as the idl on the program.
         #[clap(short, long)]
         buffer: Pubkey,
+        /// Only simulate the transaction and print the program logs.
+        #[clap(long)]
+        simulate: bool,
     },
     /// Upgrades the IDL to the new file. An alias for first writing and then
     /// then setting the idl buffer account.
@@ -71,18 +84,27 @@ pub enum IdlCommand {
 pub async fn process_idl(cmd: IdlCommand) -> CliResult {
     match cmd {
         IdlCommand::Authority { program_id } => process_authority(program_id).await,
-        IdlCommand::Close { program_id } => process_close(program_id).await,
-        IdlCommand::EraseAuthority { program_id } => process_erase_authority(program_id).await,
+        IdlCommand::Close {
+            program_id,
+            simulate,
+        } => process_close(program_id, simulate).await,
+        IdlCommand::EraseAuthority {
+            program_id,
+            simulate,
+        } => process_erase_authority(program_id, simulate).await,
         IdlCommand::Fetch { address } => process_fetch(address).await,
         IdlCommand::Init { program_id } => process_init(program_id).await,
         IdlCommand::SetAuthority {
             program_id,
             address,
             new_authority,
-        } => process_set_authority(program_id, address, new_authority).await,
-        IdlCommand::SetBuffer { program_id, buffer } => {
-            process_set_buffer(program_id, buffer).await
-        }
+            simulate,
+        } => process_set_authority(program_id, address, new_authority, simulate).await,
+        IdlCommand::SetBuffer {
+            program_id,
+            buffer,
+            simulate,
+        } => process_set_buffer(program_id, buffer, simulate).await,
         IdlCommand::Upgrade { program_id } => process_upgrade(program_id).await,
         IdlCommand::WriteBuffer { program_id } => process_write_buffer(program_id).await,
     }
@@ -110,12 +132,11 @@ async fn process_authority(program_id: Option<Pubkey>) -> CliResult {
     Ok(())
 }
 
-async fn process_close(program_id: Option<Pubkey>) -> CliResult {
+async fn process_close(program_id: Option<Pubkey>, simulate: bool) -> CliResult {
     let program_id = get_program_id(program_id)?;
     let idl_address = IdlAccount::address(&program_id);
 
-    let keypair = get_keypair();
-    let client = get_client();
+    let keypair = get_keypair()?;
 
     // Instruction accounts
     let accounts = vec![
@@ -128,49 +149,33 @@ async fn process_close(program_id: Option<Pubkey>) -> CliResult {
     let ix = Instruction {
         program_id,
         accounts,
-        data: { serialize_idl_ix(anchor_lang::idl::IdlInstruction::Close {})? },
+        data: { serialize_idl_ix(IdlInstruction::Close {})? },
     };
 
     // Send transaction
-    let latest_hash = client.get_latest_blockhash().await?;
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&keypair.pubkey()),
-        &[&keypair],
-        latest_hash,
-    );
-    client.send_and_confirm_transaction(&tx).await?;
+    if !send_or_simulate(&keypair, &[ix], simulate).await? {
+        return Ok(());
+    }
 
     PgTerminal::log_wasm(&format!("IDL account closed: {}", idl_address));
 
     Ok(())
 }
 
-async fn process_erase_authority(program_id: Option<Pubkey>) -> CliResult {
-    process_set_authority(program_id, None, ERASED_AUTHORITY).await
+async fn process_erase_authority(program_id: Option<Pubkey>, simulate: bool) -> CliResult {
+    process_set_authority(program_id, None, ERASED_AUTHORITY, simulate).await
 }
 
 async fn process_fetch(addr: Option<Pubkey>) -> CliResult {
     let addr = get_program_id(addr)?;
-    let client = get_client();
-
-    let mut account = client.get_account(&addr).await?;
-
-    if account.executable {
-        let addr = IdlAccount::address(&addr);
-        account = client.get_account(&addr).await?
-    }
+    let account = get_client().get_account(&addr).await?;
 
-    // Cut off account discriminator
-    let mut data = &account.data[8..];
-    let idl_account: IdlAccount = AnchorDeserialize::deserialize(&mut data)?;
-
-    let compressed_len: usize = idl_account.data_len.try_into().unwrap();
-    let compressed_bytes = &account.data[44..44 + compressed_len];
-    let mut z = ZlibDecoder::new(compressed_bytes);
-    let mut s = Vec::new();
-    z.read_to_end(&mut s)?;
-    let idl: Idl = serde_json::from_slice(&s[..])?;
+    let idl_address = if account.executable {
+        IdlAccount::address(&addr)
+    } else {
+        addr
+    };
+    let idl = fetch_idl(&idl_address).await?;
 
     let idl_string = serde_json::to_string_pretty(&idl)?;
     PgTerminal::log_wasm(&idl_string);
@@ -181,72 +186,14 @@ async fn process_fetch(addr: Option<Pubkey>) -> CliResult {
 async fn process_init(program_id: Option<Pubkey>) -> CliResult {
     let program_id = get_program_id(program_id)?;
     let idl = get_idl()?;
-    let idl_address = IdlAccount::address(&program_id);
-    let idl_data = serialize_idl(&idl)?;
-
-    let keypair = get_keypair();
-    let client = get_client();
-
-    // Run `Create instruction
-    {
-        let pda_max_growth = 60_000;
-        let idl_header_size = 44;
-        let idl_data_len = idl_data.len() as u64;
-        // We're only going to support up to 6 instructions in one transaction
-        // because will anyone really have a >60kb IDL?
-        if idl_data_len > pda_max_growth {
-            return Err(anyhow!(
-                "Your IDL is over 60kb and this isn't supported right now"
-            ));
-        }
-        // Double for future growth
-        let data_len = (idl_data_len * 2).min(pda_max_growth - idl_header_size);
-
-        let num_additional_instructions = data_len / 10000;
-        let mut instructions = Vec::new();
-        let data = serialize_idl_ix(anchor_lang::idl::IdlInstruction::Create { data_len })?;
-        let program_signer = Pubkey::find_program_address(&[], &program_id).0;
-        let accounts = vec![
-            AccountMeta::new_readonly(keypair.pubkey(), true),
-            AccountMeta::new(idl_address, false),
-            AccountMeta::new_readonly(program_signer, false),
-            AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(program_id, false),
-            AccountMeta::new_readonly(sysvar::rent::ID, false),
-        ];
-        instructions.push(Instruction {
-            program_id,
-            accounts,
-            data,
-        });
-
-        for _ in 0..num_additional_instructions {
-            let data = serialize_idl_ix(anchor_lang::idl::IdlInstruction::Resize { data_len })?;
-            instructions.push(Instruction {
-                program_id,
-                accounts: vec![
-                    AccountMeta::new(idl_address, false),
-                    AccountMeta::new_readonly(keypair.pubkey(), true),
-                    AccountMeta::new_readonly(system_program::ID, false),
-                ],
-                data,
-            });
-        }
-
-        let latest_hash = client.get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&keypair.pubkey()),
-            &[&keypair],
-            latest_hash,
-        );
-        client.send_and_confirm_transaction(&tx).await?;
-    }
+    ensure_program_deployed(&program_id).await?;
 
-    // Write directly to the IDL account buffer
-    idl_write(program_id, &idl, IdlAccount::address(&program_id)).await?;
+    publish_idl(&get_keypair()?, &program_id, &idl).await?;
 
-    PgTerminal::log_wasm(&format!("IDL account created: {}", idl_address));
+    PgTerminal::log_wasm(&format!(
+        "IDL account created: {}",
+        IdlAccount::address(&program_id)
+    ));
 
     Ok(())
 }
@@ -255,15 +202,15 @@ async fn process_set_authority(
     program_id: Option<Pubkey>,
     idl_address: Option<Pubkey>,
     new_authority: Pubkey,
+    simulate: bool,
 ) -> CliResult {
     let program_id = get_program_id(program_id)?;
     let idl_address = idl_address.unwrap_or(IdlAccount::address(&program_id));
 
-    let keypair = get_keypair();
-    let client = get_client();
+    let keypair = get_keypair()?;
 
     // Instruction data
-    let data = serialize_idl_ix(anchor_lang::idl::IdlInstruction::SetAuthority { new_authority })?;
+    let data = serialize_idl_ix(IdlInstruction::SetAuthority { new_authority })?;
 
     // Instruction accounts
     let accounts = vec![
@@ -279,14 +226,9 @@ async fn process_set_authority(
     };
 
     // Send transaction
-    let latest_hash = client.get_latest_blockhash().await?;
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&keypair.pubkey()),
-        &[&keypair],
-        latest_hash,
-    );
-    client.send_and_confirm_transaction(&tx).await?;
+    if !send_or_simulate(&keypair, &[ix], simulate).await? {
+        return Ok(());
+    }
 
     if new_authority == ERASED_AUTHORITY {
         PgTerminal::log_wasm("Erased authority.");
@@ -297,11 +239,14 @@ async fn process_set_authority(
     Ok(())
 }
 
-async fn process_set_buffer(program_id: Option<Pubkey>, buffer: Pubkey) -> CliResult {
+async fn process_set_buffer(
+    program_id: Option<Pubkey>,
+    buffer: Pubkey,
+    simulate: bool,
+) -> CliResult {
     let program_id = get_program_id(program_id)?;
 
-    let keypair = get_keypair();
-    let client = get_client();
+    let keypair = get_keypair()?;
 
     // Instruction to set the buffer onto the IdlAccount
     let set_buffer_ix = {
@@ -310,26 +255,15 @@ async fn process_set_buffer(program_id: Option<Pubkey>, buffer: Pubkey) -> CliRe
             AccountMeta::new(IdlAccount::address(&program_id), false),
             AccountMeta::new(keypair.pubkey(), true),
         ];
-        let mut data = anchor_lang::idl::IDL_IX_TAG.to_le_bytes().to_vec();
-        data.append(&mut IdlInstruction::SetBuffer.try_to_vec()?);
         Instruction {
             program_id,
             accounts,
-            data,
+            data: serialize_idl_ix(IdlInstruction::SetBuffer)?,
         }
     };
 
-    // Build the transaction
-    let latest_hash = client.get_latest_blockhash().await?;
-    let tx = Transaction::new_signed_with_payer(
-        &[set_buffer_ix],
-        Some(&keypair.pubkey()),
-        &[&keypair],
-        latest_hash,
-    );
-
     // Send the transaction
-    client.send_and_confirm_transaction(&tx).await?;
+    send_or_simulate(&keypair, &[set_buffer_ix], simulate).await?;
 
     Ok(())
 }
@@ -337,7 +271,7 @@ async fn process_set_buffer(program_id: Option<Pubkey>, buffer: Pubkey) -> CliRe
 async fn process_upgrade(program_id: Option<Pubkey>) -> CliResult {
     let program_id = get_program_id(program_id)?;
     let buffer_pk = create_and_write_buffer(program_id).await?;
-    process_set_buffer(Some(program_id), buffer_pk).await
+    process_set_buffer(Some(program_id), buffer_pk, false).await
 }
 
 async fn process_write_buffer(program_id: Option<Pubkey>) -> CliResult {
@@ -346,77 +280,29 @@ async fn process_write_buffer(program_id: Option<Pubkey>) -> CliResult {
     Ok(())
 }
 
-/// Write the idl to the account buffer, chopping up the IDL into pieces and sending multiple
-/// transactions in the event the IDL doesn't fit into a single transaction
-async fn idl_write(program_id: Pubkey, idl: &Idl, idl_address: Pubkey) -> CliResult {
-    let keypair = get_keypair();
-    let client = get_client();
-
-    // Remove the metadata before deploy
-    let mut idl = idl.clone();
-    idl.metadata = None;
-
-    // Serialize and compress the idl
-    let idl_data = {
-        let json_bytes = serde_json::to_vec(&idl)?;
-        let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
-        e.write_all(&json_bytes)?;
-        e.finish()?
-    };
-
-    const MAX_WRITE_SIZE: usize = 1000;
-    let mut offset = 0;
-    while offset < idl_data.len() {
-        // Instruction data
-        let data = {
-            let start = offset;
-            let end = std::cmp::min(offset + MAX_WRITE_SIZE, idl_data.len());
-            serialize_idl_ix(anchor_lang::idl::IdlInstruction::Write {
-                data: idl_data[start..end].to_vec(),
-            })?
-        };
-        // Instruction accounts
-        let accounts = vec![
-            AccountMeta::new(idl_address, false),
-            AccountMeta::new_readonly(keypair.pubkey(), true),
-        ];
-        // Instruction
-        let ix = Instruction {
-            program_id,
-            accounts,
-            data,
-        };
-        // Send transaction
-        let latest_hash = client.get_latest_blockhash().await?;
-        let tx = Transaction::new_signed_with_payer(
-            &[ix],
-            Some(&keypair.pubkey()),
-            &[&keypair],
-            latest_hash,
-        );
-        client.send_and_confirm_transaction(&tx).await?;
-        offset += MAX_WRITE_SIZE;
+/// Send and confirm the instructions, or only simulate them and log the result if `simulate` is
+/// set.
+///
+/// Returns whether the transaction was sent.
+async fn send_or_simulate(
+    payer: &dyn signer::PgSigner,
+    ixs: &[Instruction],
+    simulate: bool,
+) -> CliResult<bool> {
+    if simulate {
+        let result = simulate_instructions(payer, ixs, &[]).await?;
+        log_simulation_result(&result);
+        return Ok(false);
     }
-    Ok(())
-}
-
-/// Serialize and compress the idl
-fn serialize_idl(idl: &Idl) -> CliResult<Vec<u8>> {
-    let json_bytes = serde_json::to_vec(idl)?;
-    let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
-    e.write_all(&json_bytes)?;
-    e.finish().map_err(Into::into)
-}
 
-fn serialize_idl_ix(ix_inner: anchor_lang::idl::IdlInstruction) -> CliResult<Vec<u8>> {
-    let mut data = anchor_lang::idl::IDL_IX_TAG.to_le_bytes().to_vec();
-    data.append(&mut ix_inner.try_to_vec()?);
-    Ok(data)
+    send_and_confirm(payer, ixs, &[]).await?;
+    Ok(true)
 }
 
 async fn create_and_write_buffer(program_id: Pubkey) -> CliResult<Pubkey> {
-    let idl = get_idl()?;
-    let keypair = get_keypair();
+    ensure_program_deployed(&program_id).await?;
+    let idl_data = serialize_idl(&get_idl()?)?;
+    let keypair = get_keypair()?;
     let client = get_client();
 
     let buffer_kp = Keypair::new();
@@ -424,7 +310,7 @@ async fn create_and_write_buffer(program_id: Pubkey) -> CliResult<Pubkey> {
 
     // Creates the new buffer account with the system program
     let create_account_ix = {
-        let space = 8 + 32 + 4 + serialize_idl(&idl)?.len();
+        let space = 8 + 32 + 4 + idl_data.len();
         let lamports = client.get_minimum_balance_for_rent_exemption(space).await?;
         solana_sdk::system_instruction::create_account(
             &keypair.pubkey(),
@@ -442,28 +328,22 @@ async fn create_and_write_buffer(program_id: Pubkey) -> CliResult<Pubkey> {
             AccountMeta::new_readonly(keypair.pubkey(), true),
             AccountMeta::new_readonly(sysvar::rent::ID, false),
         ];
-        let mut data = anchor_lang::idl::IDL_IX_TAG.to_le_bytes().to_vec();
-        data.append(&mut IdlInstruction::CreateBuffer.try_to_vec()?);
         Instruction {
             program_id,
             accounts,
-            data,
+            data: serialize_idl_ix(IdlInstruction::CreateBuffer)?,
         }
     };
 
-    // Build the transaction
-    let latest_hash = client.get_latest_blockhash().await?;
-    let tx = Transaction::new_signed_with_payer(
-        &[create_account_ix, create_buffer_ix],
-        Some(&keypair.pubkey()),
-        &[&keypair, &buffer_kp],
-        latest_hash,
-    );
-
     // Send the transaction
-    client.send_and_confirm_transaction(&tx).await?;
+    send_and_confirm(
+        &keypair,
+        &[create_account_ix, create_buffer_ix],
+        &[&buffer_kp],
+    )
+    .await?;
 
-    idl_write(program_id, &idl, buffer_pk).await?;
+    write_idl(&keypair, &program_id, &buffer_pk, &idl_data).await?;
 
     PgTerminal::log_wasm(&format!("IDL buffer created: {}", buffer_pk));
 
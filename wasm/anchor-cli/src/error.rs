@@ -0,0 +1,153 @@
+use std::fmt;
+
+use solana_client_wasm::ClientError;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature};
+
+/// Code of the RPC client errors
+const RPC_ERROR_CODE: &str = "RPC";
+
+/// Playground errors with stable codes so that the JS side can react to specific failures
+#[derive(Debug)]
+pub enum PgError {
+    NoIdl,
+    NoProgramId,
+    InvalidPubkey {
+        value: String,
+        reason: String,
+    },
+    InvalidProgramId {
+        value: String,
+        reason: String,
+    },
+    InvalidCommitment(String),
+    InvalidCluster(String),
+    WalletNotFound(String),
+    InvalidKeypair(String),
+    NotDeployed(Pubkey),
+    BlockhashExpired(Hash),
+    ConnectionFailed {
+        endpoint: String,
+        reason: String,
+    },
+    NotConfirmed {
+        signature: Signature,
+        timeout_ms: u64,
+    },
+    AirdropNotConfirmed {
+        timeout_ms: u64,
+    },
+    TransactionFailed(String),
+    TransactionExpired(Signature),
+}
+
+impl PgError {
+    /// Machine readable code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoIdl => "NO_IDL",
+            Self::NoProgramId => "NO_PROGRAM_ID",
+            Self::InvalidPubkey { .. } => "INVALID_PUBKEY",
+            Self::InvalidProgramId { .. } => "INVALID_PROGRAM_ID",
+            Self::InvalidCommitment(_) => "INVALID_COMMITMENT",
+            Self::InvalidCluster(_) => "INVALID_CLUSTER",
+            Self::WalletNotFound(_) => "WALLET_NOT_FOUND",
+            Self::InvalidKeypair(_) => "INVALID_KEYPAIR",
+            Self::NotDeployed(_) => "NOT_DEPLOYED",
+            Self::BlockhashExpired(_) => "BLOCKHASH_EXPIRED",
+            Self::ConnectionFailed { .. } => "CONNECTION_FAILED",
+            Self::NotConfirmed { .. } | Self::AirdropNotConfirmed { .. } => "NOT_CONFIRMED",
+            Self::TransactionFailed(_) => "TRANSACTION_FAILED",
+            Self::TransactionExpired(_) => "TRANSACTION_EXPIRED",
+        }
+    }
+}
+
+impl fmt::Display for PgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoIdl => write!(f, "IDL not found"),
+            Self::NoProgramId => write!(f, "Program id doesn't exist"),
+            Self::InvalidPubkey { value, reason } => {
+                write!(f, "Invalid public key '{value}': {reason}")
+            }
+            Self::InvalidProgramId { value, reason } => {
+                write!(f, "Invalid program id '{value}': {reason}")
+            }
+            Self::InvalidCommitment(commitment) => write!(f, "Invalid commitment: {commitment}"),
+            Self::InvalidCluster(cluster) => write!(f, "Invalid cluster: {cluster}"),
+            Self::WalletNotFound(name) => write!(f, "Wallet '{name}' not found"),
+            Self::InvalidKeypair(reason) => write!(f, "Invalid wallet keypair bytes: {reason}"),
+            Self::NotDeployed(program_id) => write!(f, "{program_id} is not a deployed program"),
+            Self::BlockhashExpired(blockhash) => write!(
+                f,
+                "Blockhash {blockhash} has expired, refresh the blockhash and try again"
+            ),
+            Self::ConnectionFailed { endpoint, reason } => {
+                write!(f, "Cannot reach {endpoint}: {reason}")
+            }
+            Self::NotConfirmed {
+                signature,
+                timeout_ms,
+            } => write!(
+                f,
+                "Transaction {signature} not confirmed within {timeout_ms}ms"
+            ),
+            Self::AirdropNotConfirmed { timeout_ms } => {
+                write!(f, "Airdrop not confirmed after {}s", timeout_ms / 1000)
+            }
+            Self::TransactionFailed(reason) => write!(f, "Transaction failed: {reason}"),
+            Self::TransactionExpired(signature) => {
+                write!(f, "Transaction {signature} expired, please retry")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgError {}
+
+/// Get the code of the error if it's a [`PgError`] or an RPC error
+pub fn error_code(err: &anyhow::Error) -> Option<&'static str> {
+    if let Some(err) = err.downcast_ref::<PgError>() {
+        return Some(err.code());
+    }
+    if err.downcast_ref::<ClientError>().is_some() {
+        return Some(RPC_ERROR_CODE);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        let err = anyhow::Error::from(PgError::NoIdl);
+        assert_eq!(error_code(&err), Some("NO_IDL"));
+        assert_eq!(err.to_string(), "IDL not found");
+
+        let err = anyhow::Error::from(ClientError::new("Connection refused"));
+        assert_eq!(error_code(&err), Some("RPC"));
+
+        let err = anyhow::Error::from(PgError::InvalidPubkey {
+            value: "abc".to_owned(),
+            reason: "Invalid Base58 string".to_owned(),
+        });
+        assert_eq!(error_code(&err), Some("INVALID_PUBKEY"));
+        assert_eq!(
+            err.to_string(),
+            "Invalid public key 'abc': Invalid Base58 string"
+        );
+
+        let err = anyhow::anyhow!("Something else");
+        assert_eq!(error_code(&err), None);
+    }
+
+    #[test]
+    fn test_airdrop_not_confirmed() {
+        let err = PgError::AirdropNotConfirmed { timeout_ms: 30_000 };
+        assert_eq!(err.code(), "NOT_CONFIRMED");
+        assert_eq!(err.to_string(), "Airdrop not confirmed after 30s");
+    }
+}
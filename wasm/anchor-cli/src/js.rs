@@ -9,14 +9,38 @@ extern "C" {
 
     #[wasm_bindgen(static_method_of = PgConnection, getter)]
     pub fn commitment() -> String;
+
+    #[wasm_bindgen(static_method_of = PgConnection, getter, js_name = "confirmCommitment")]
+    pub fn confirm_commitment() -> Option<String>;
+
+    #[wasm_bindgen(static_method_of = PgConnection, getter, js_name = "retryCount")]
+    pub fn retry_count() -> u32;
+
+    #[wasm_bindgen(static_method_of = PgConnection, getter, js_name = "headersStr")]
+    pub fn headers_string() -> Option<String>;
+
+    #[wasm_bindgen(static_method_of = PgConnection, getter, js_name = "priorityFee")]
+    pub fn priority_fee() -> u64;
 }
 
 #[wasm_bindgen(raw_module = "/src/utils/pg/wallet.ts")]
 extern "C" {
     pub type PgWallet;
 
-    #[wasm_bindgen(static_method_of = PgWallet, getter, js_name = "keypairBytes")]
-    pub fn keypair_bytes() -> Vec<u8>;
+    #[wasm_bindgen(static_method_of = PgWallet, getter, js_name = "currentName")]
+    pub fn current_name() -> String;
+
+    #[wasm_bindgen(static_method_of = PgWallet, js_name = "keypairBytesFor")]
+    pub fn keypair_bytes_for(name: &str) -> Option<Vec<u8>>;
+
+    #[wasm_bindgen(static_method_of = PgWallet, getter, js_name = "externalPkStr")]
+    pub fn external_pk_string() -> Option<String>;
+
+    #[wasm_bindgen(static_method_of = PgWallet, js_name = "externalSign", catch)]
+    pub async fn external_sign(msg: &[u8]) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(static_method_of = PgWallet, js_name = "externalSignTransaction", catch)]
+    pub async fn external_sign_transaction(message_data: &[u8]) -> Result<JsValue, JsValue>;
 }
 
 #[wasm_bindgen(raw_module = "/src/utils/pg/program-info.ts")]
@@ -1,4 +1,7 @@
 pub mod cli;
+mod coder;
 mod commands;
+pub mod error;
 mod js;
-mod utils;
+pub mod signer;
+pub mod utils;
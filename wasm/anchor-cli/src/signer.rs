@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use wasm_bindgen::JsValue;
+
+use crate::{cli::CliResult, error::PgError, js::PgWallet};
+
+/// Signer that can either be a local keypair or an external wallet.
+///
+/// Signing is async because external wallets need the user's approval.
+#[async_trait(?Send)]
+pub trait PgSigner {
+    fn pubkey(&self) -> Pubkey;
+
+    async fn sign_message(&self, msg: &[u8]) -> CliResult<Signature>;
+
+    /// Sign the serialized transaction message.
+    ///
+    /// Same as `sign_message` by default, but external wallets refuse to sign transactions as
+    /// messages.
+    async fn sign_transaction_message(&self, message_data: &[u8]) -> CliResult<Signature> {
+        self.sign_message(message_data).await
+    }
+}
+
+#[async_trait(?Send)]
+impl PgSigner for Keypair {
+    fn pubkey(&self) -> Pubkey {
+        Signer::pubkey(self)
+    }
+
+    async fn sign_message(&self, msg: &[u8]) -> CliResult<Signature> {
+        Ok(Signer::sign_message(self, msg))
+    }
+}
+
+/// Browser wallet(Phantom, Backpack...) that is connected to the playground
+pub struct ExternalSigner {
+    pubkey: Pubkey,
+}
+
+impl ExternalSigner {
+    /// Get the currently connected external wallet
+    pub fn connected() -> CliResult<Self> {
+        let pk_string = PgWallet::external_pk_string()
+            .ok_or_else(|| anyhow!("External wallet is not connected"))?;
+        let pubkey = Pubkey::from_str(&pk_string).map_err(|e| PgError::InvalidPubkey {
+            value: pk_string.clone(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(Self { pubkey })
+    }
+}
+
+#[async_trait(?Send)]
+impl PgSigner for ExternalSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, msg: &[u8]) -> CliResult<Signature> {
+        let signature = PgWallet::external_sign(msg)
+            .await
+            .map_err(|e| anyhow!("External wallet failed to sign: {e:?}"))?;
+        parse_signature(&signature)
+    }
+
+    async fn sign_transaction_message(&self, message_data: &[u8]) -> CliResult<Signature> {
+        let signature = PgWallet::external_sign_transaction(message_data)
+            .await
+            .map_err(|e| anyhow!("External wallet failed to sign the transaction: {e:?}"))?;
+        check_transaction_signature(&self.pubkey, parse_signature(&signature)?, message_data)
+    }
+}
+
+/// Check that the wallet signed the given transaction message.
+///
+/// Wallets that modify the transaction before signing, e.g. by adding instructions or changing
+/// the fee payer, return a signature of a different message which the RPC rejects.
+fn check_transaction_signature(
+    pubkey: &Pubkey,
+    signature: Signature,
+    message_data: &[u8],
+) -> CliResult<Signature> {
+    if !signature.verify(pubkey.as_ref(), message_data) {
+        return Err(anyhow!(
+            "External wallet signature doesn't match the transaction, \
+            the wallet might have modified the transaction before signing it"
+        ));
+    }
+
+    Ok(signature)
+}
+
+/// Parse the signature bytes returned from JS
+fn parse_signature(signature: &JsValue) -> CliResult<Signature> {
+    let signature_bytes = js_sys::Uint8Array::new(signature).to_vec();
+    Signature::try_from(signature_bytes.as_slice())
+        .map_err(|_| anyhow!("Invalid signature length: {}", signature_bytes.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keypair_signer() {
+        let keypair = Keypair::new();
+        let signer: &dyn PgSigner = &keypair;
+        let msg = b"hello";
+        let signature = futures::executor::block_on(signer.sign_message(msg)).unwrap();
+
+        assert_eq!(signer.pubkey(), Signer::pubkey(&keypair));
+        assert!(signature.verify(signer.pubkey().as_ref(), msg));
+        assert_eq!(
+            futures::executor::block_on(signer.sign_transaction_message(msg)).unwrap(),
+            signature
+        );
+    }
+
+    #[test]
+    fn test_check_transaction_signature() {
+        let keypair = Keypair::new();
+        let pubkey = Signer::pubkey(&keypair);
+        let message_data = b"message";
+        let signature = Signer::sign_message(&keypair, message_data);
+        assert_eq!(
+            check_transaction_signature(&pubkey, signature, message_data).unwrap(),
+            signature
+        );
+
+        // Signature of a modified message
+        let signature = Signer::sign_message(&keypair, b"modified message");
+        assert!(check_transaction_signature(&pubkey, signature, message_data).is_err());
+    }
+}
@@ -1,50 +1,3591 @@
-use std::str::FromStr;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    future::Future,
+    io::{Read, Write},
+    pin::Pin,
+    rc::Rc,
+    str::FromStr,
+};
 
-use anchor_lang::prelude::Pubkey;
-use anchor_syn::idl::Idl;
+use anchor_lang::{
+    error::ErrorCode,
+    idl::{IdlAccount, IdlInstruction, IDL_IX_TAG},
+    prelude::Pubkey,
+    AccountDeserialize, AnchorDeserialize, AnchorSerialize,
+};
+use anchor_syn::idl::{Idl, IdlAccountItem, IdlPda, IdlSeed, IdlType};
 use anyhow::anyhow;
-use solana_client_wasm::WasmClient;
+use bip39::{Language, Mnemonic, Seed};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use solana_client_wasm::{
+    utils::{
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+        rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+        rpc_request::{MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_MULTIPLE_ACCOUNTS},
+        rpc_response::RpcSimulateTransactionResult,
+    },
+    ClientError, ClientResult, SignatureStatusesValue, WasmClient,
+};
+use solana_extra_wasm::{
+    account_decoder::{UiAccount, UiAccountEncoding},
+    program::{spl_associated_token_account, spl_memo, spl_token},
+    transaction_status::{
+        EncodedConfirmedTransactionWithStatusMeta, TransactionConfirmationStatus,
+        UiTransactionEncoding,
+    },
+    utils::sleep,
+};
+use solana_playground_utils_wasm::js::PgTerminal;
 use solana_sdk::{
+    account::Account,
+    address_lookup_table_account::AddressLookupTableAccount,
     commitment_config::{CommitmentConfig, CommitmentLevel},
-    signature::Keypair,
+    compute_budget::{self, ComputeBudgetInstruction},
+    derivation_path::DerivationPath,
+    hash::{hash, Hash},
+    instruction::{AccountMeta, Instruction},
+    message::{v0, Message, VersionedMessage},
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey,
+    signature::{keypair_from_seed, keypair_from_seed_and_derivation_path, Keypair, Signature},
+    signer::Signer,
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError, VersionedTransaction},
 };
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{MessageEvent, WebSocket};
 
 use crate::{
     cli::CliResult,
+    coder,
+    error::PgError,
     js::{PgConnection, PgProgramInfo, PgWallet},
+    signer,
 };
 
 pub fn get_client() -> WasmClient {
-    WasmClient::new_with_commitment(
-        &PgConnection::endpoint(),
-        CommitmentConfig {
-            commitment: match PgConnection::commitment().as_str() {
-                "processed" => CommitmentLevel::Processed,
-                "confirmed" => CommitmentLevel::Confirmed,
-                "finalized" => CommitmentLevel::Finalized,
-                _ => CommitmentLevel::Confirmed,
-            },
-        },
-    )
+    get_client_with_endpoint(&PgConnection::endpoint(), get_commitment())
+}
+
+/// Get a client with the given commitment instead of the configured commitment
+pub fn get_client_with_commitment(level: CommitmentLevel) -> WasmClient {
+    get_client_with_endpoint(&PgConnection::endpoint(), level)
+}
+
+/// Check whether the configured endpoint is reachable, returns the current slot if it is
+pub async fn check_connection() -> CliResult<u64> {
+    let endpoint = PgConnection::endpoint();
+    let client = get_client_with_endpoint(&endpoint, get_commitment());
+    check_connection_with(&endpoint, client.get_slot()).await
+}
+
+async fn check_connection_with<Fut>(endpoint: &str, get_slot: Fut) -> CliResult<u64>
+where
+    Fut: Future<Output = ClientResult<u64>>,
+{
+    get_slot.await.map_err(|e| {
+        PgError::ConnectionFailed {
+            endpoint: endpoint.to_owned(),
+            reason: e.message().to_owned(),
+        }
+        .into()
+    })
+}
+
+pub async fn get_slot() -> CliResult<u64> {
+    Ok(get_client().get_slot().await?)
+}
+
+/// Get the estimated production time of the block as a Unix timestamp
+pub async fn get_block_time(slot: u64) -> CliResult<i64> {
+    let block_time = get_client().try_get_block_time(slot).await?;
+    block_time_or_err(slot, block_time)
+}
+
+fn block_time_or_err(slot: u64, block_time: Option<i64>) -> CliResult<i64> {
+    block_time.ok_or_else(|| {
+        anyhow!(
+            "Block time of slot {slot} is not available, \
+            the slot might have been skipped or pruned from the RPC node's ledger"
+        )
+    })
+}
+
+/// Information about the cluster of the configured endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterInfo {
+    /// Version of the RPC node
+    pub version: String,
+    pub genesis_hash: Hash,
+    pub slot: u64,
+}
+
+pub async fn get_cluster_info() -> CliResult<ClusterInfo> {
+    let endpoint = PgConnection::endpoint();
+    let client = get_client_with_endpoint(&endpoint, get_commitment());
+    let err = |name: &str, e: ClientError| anyhow!("Failed to get the {name} from {endpoint}: {e}");
+
+    let version = client
+        .get_version()
+        .await
+        .map_err(|e| err("version", e))?
+        .solana_core;
+    let genesis_hash = client
+        .get_genesis_hash()
+        .await
+        .map_err(|e| err("genesis hash", e))?;
+    let slot = client.get_slot().await.map_err(|e| err("slot", e))?;
+
+    Ok(ClusterInfo {
+        version,
+        genesis_hash,
+        slot,
+    })
+}
+
+/// Get a client for the given cluster instead of the configured endpoint
+pub fn get_client_for(cluster: &Cluster) -> WasmClient {
+    get_client_with_endpoint(&cluster.endpoint(), get_commitment())
+}
+
+/// Get the configured commitment, defaults to `confirmed` if it's invalid
+fn get_commitment() -> CommitmentLevel {
+    let commitment = PgConnection::commitment();
+    parse_commitment(&commitment).unwrap_or_else(|_| {
+        log_warning_once(format!(
+            "unknown commitment '{commitment}', defaulting to 'confirmed'"
+        ));
+        CommitmentLevel::Confirmed
+    })
+}
+
+thread_local! {
+    /// Warnings that have already been shown in the terminal
+    static SHOWN_WARNINGS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+/// Log the warning unless the same warning has already been shown, e.g. the warnings of the
+/// connection settings that are read each time a client is created
+fn log_warning_once(warning: String) {
+    if mark_warning_shown(&warning) {
+        PgTerminal::log_wasm(&format!("Warning: {warning}"));
+    }
+}
+
+/// Remember the warning, returns whether it's the first time the warning is seen
+fn mark_warning_shown(warning: &str) -> bool {
+    SHOWN_WARNINGS.with(|shown| shown.borrow_mut().insert(warning.to_owned()))
+}
+
+fn get_client_with_endpoint(endpoint: &str, commitment: CommitmentLevel) -> WasmClient {
+    create_client(endpoint, commitment, &get_headers())
+}
+
+fn create_client(
+    endpoint: &str,
+    commitment: CommitmentLevel,
+    headers: &[(String, String)],
+) -> WasmClient {
+    let commitment_config = CommitmentConfig { commitment };
+    WasmClient::new_with_headers(endpoint, commitment_config, headers).unwrap_or_else(|e| {
+        log_warning_once(format!("{e}, ignoring custom headers"));
+        WasmClient::new_with_commitment(endpoint, commitment_config)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cluster {
+    Localnet,
+    Devnet,
+    Testnet,
+    MainnetBeta,
+    Custom(String),
+}
+
+impl Cluster {
+    pub fn endpoint(&self) -> String {
+        match self {
+            Self::Localnet => "http://localhost:8899",
+            Self::Devnet => "https://api.devnet.solana.com",
+            Self::Testnet => "https://api.testnet.solana.com",
+            Self::MainnetBeta => "https://api.mainnet-beta.solana.com",
+            Self::Custom(endpoint) => endpoint.as_str(),
+        }
+        .to_owned()
+    }
+}
+
+impl FromStr for Cluster {
+    type Err = anyhow::Error;
+
+    /// Parse the cluster from its name, moniker or a custom URL
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "l" | "localnet" | "localhost" => Ok(Self::Localnet),
+            "d" | "devnet" => Ok(Self::Devnet),
+            "t" | "testnet" => Ok(Self::Testnet),
+            "m" | "mainnet" | "mainnet-beta" => Ok(Self::MainnetBeta),
+            url if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(Self::Custom(url.to_owned()))
+            }
+            _ => Err(PgError::InvalidCluster(s.to_owned()).into()),
+        }
+    }
+}
+
+/// Get the custom RPC headers, e.g. API keys of authenticated endpoints
+pub fn get_headers() -> Vec<(String, String)> {
+    PgConnection::headers_string()
+        .map(|headers_string| {
+            parse_headers(&headers_string).unwrap_or_else(|e| {
+                log_warning_once(format!("{e}, ignoring custom headers"));
+                vec![]
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Parse the custom headers from a JSON object of header names to values
+fn parse_headers(headers_string: &str) -> CliResult<Vec<(String, String)>> {
+    serde_json::from_str::<HashMap<String, String>>(headers_string)
+        .map(|headers| headers.into_iter().collect())
+        .map_err(|e| anyhow!("Invalid custom headers JSON: {e}"))
+}
+
+pub fn parse_commitment(commitment: &str) -> CliResult<CommitmentLevel> {
+    match commitment {
+        "processed" => Ok(CommitmentLevel::Processed),
+        "confirmed" => Ok(CommitmentLevel::Confirmed),
+        "finalized" => Ok(CommitmentLevel::Finalized),
+        _ => Err(PgError::InvalidCommitment(commitment.to_owned()).into()),
+    }
+}
+
+pub fn get_keypair() -> CliResult<Keypair> {
+    get_keypair_by_name(&PgWallet::current_name())
+}
+
+pub fn get_keypair_by_name(name: &str) -> CliResult<Keypair> {
+    match PgWallet::keypair_bytes_for(name) {
+        Some(keypair_bytes) => keypair_from_bytes(&keypair_bytes),
+        None => Err(PgError::WalletNotFound(name.to_owned()).into()),
+    }
+}
+
+fn keypair_from_bytes(bytes: &[u8]) -> CliResult<Keypair> {
+    if bytes.len() != 64 {
+        return Err(
+            PgError::InvalidKeypair(format!("expected 64 bytes, got {}", bytes.len())).into(),
+        );
+    }
+
+    Keypair::from_bytes(bytes).map_err(|e| PgError::InvalidKeypair(e.to_string()).into())
+}
+
+/// Serialize the keypair to the JSON array format used by `solana-keygen`
+pub fn keypair_to_json(kp: &Keypair) -> String {
+    serde_json::to_string(&kp.to_bytes().to_vec()).unwrap()
+}
+
+/// Parse the keypair from the JSON array format used by `solana-keygen`
+pub fn keypair_from_json(s: &str) -> CliResult<Keypair> {
+    let bytes: Vec<u8> =
+        serde_json::from_str(s).map_err(|e| anyhow!("Invalid keypair JSON: {e}"))?;
+    keypair_from_bytes(&bytes)
+}
+
+/// Derive a deterministic keypair from `sha256(seed)`, e.g. the same "alice" keypair every run.
+///
+/// NOTE: Anyone who knows the seed string can derive the keypair, never use it for real funds.
+pub fn keypair_from_seed_string(seed: &str) -> Keypair {
+    keypair_from_seed(&hash(seed.as_bytes()).to_bytes()).unwrap()
 }
 
-pub fn get_keypair() -> Keypair {
-    Keypair::from_bytes(&PgWallet::keypair_bytes()).unwrap()
+/// Derive the keypair from the BIP39 mnemonic with the default Solana derivation path
+pub fn keypair_from_mnemonic(phrase: &str, passphrase: &str) -> CliResult<Keypair> {
+    keypair_from_mnemonic_with_path(phrase, passphrase, "m/44'/501'/0'/0'")
+}
+
+pub fn keypair_from_mnemonic_with_path(
+    phrase: &str,
+    passphrase: &str,
+    derivation_path: &str,
+) -> CliResult<Keypair> {
+    let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+        .map_err(|_| anyhow!("Invalid mnemonic"))?;
+    let seed = Seed::new(&mnemonic, passphrase);
+    let derivation_path = DerivationPath::from_absolute_path_str(derivation_path)
+        .map_err(|e| anyhow!("Invalid derivation path '{derivation_path}': {e}"))?;
+
+    keypair_from_seed_and_derivation_path(seed.as_bytes(), Some(derivation_path))
+        .map_err(|e| anyhow!("Failed to derive keypair: {e}"))
+}
+
+/// Sign an arbitrary off-chain message, e.g. a login nonce
+pub fn sign_message(kp: &Keypair, message: &[u8]) -> Signature {
+    kp.sign_message(message)
+}
+
+/// Verify the ed25519 signature of the message
+pub fn verify_signature(pubkey: &Pubkey, message: &[u8], sig: &Signature) -> bool {
+    sig.verify(pubkey.as_ref(), message)
 }
 
 pub fn get_idl() -> CliResult<Idl> {
-    match PgProgramInfo::idl_string().map(|idl_string| serde_json::from_str(&idl_string).unwrap()) {
-        Some(idl) => Ok(idl),
-        None => Err(anyhow!("IDL not found")),
+    match PgProgramInfo::idl_string() {
+        Some(idl_string) => parse_idl_cached(idl_string),
+        None => Err(PgError::NoIdl.into()),
+    }
+}
+
+pub fn parse_idl(idl_string: &str) -> CliResult<Idl> {
+    serde_json::from_str(idl_string).map_err(|e| anyhow!("Failed to parse IDL: {e}"))
+}
+
+/// Get the IDL from the local storage, or fetch it from the chain if it doesn't exist locally
+pub async fn get_idl_or_fetch(program_id: &Pubkey) -> CliResult<Idl> {
+    match PgProgramInfo::idl_string() {
+        Some(idl_string) => parse_idl_cached(idl_string),
+        None => fetch_idl(&IdlAccount::address(program_id)).await,
+    }
+}
+
+/// Fetch and decode the IDL stored in the given IDL account
+pub async fn fetch_idl(idl_address: &Pubkey) -> CliResult<Idl> {
+    let client = get_client();
+    let account = client
+        .get_account_with_commitment(idl_address, client.commitment_config())
+        .await?
+        .ok_or_else(|| anyhow!("IDL account {idl_address} not found"))?;
+
+    // Cut off account discriminator
+    let mut data = account
+        .data
+        .get(8..)
+        .ok_or_else(|| anyhow!("Invalid IDL account data"))?;
+    let idl_account: IdlAccount = AnchorDeserialize::deserialize(&mut data)?;
+
+    let compressed_len = idl_account.data_len as usize;
+    let compressed_bytes = account
+        .data
+        .get(44..44 + compressed_len)
+        .ok_or_else(|| anyhow!("Invalid IDL account data length"))?;
+    let mut z = ZlibDecoder::new(compressed_bytes);
+    let mut s = Vec::new();
+    z.read_to_end(&mut s)
+        .map_err(|e| anyhow!("Failed to inflate IDL data: {e}"))?;
+
+    serde_json::from_slice(&s).map_err(|e| anyhow!("Failed to deserialize IDL: {e}"))
+}
+
+/// Maximum IDL account data length
+const IDL_MAX_DATA_LEN: u64 = 60_000;
+/// Size of the IDL account header (discriminator, authority and data length)
+const IDL_HEADER_SIZE: u64 = 44;
+/// Maximum amount of account data that can be allocated with a single instruction
+const IDL_MAX_ALLOCATION: u64 = 10_000;
+/// Maximum amount of IDL data that fits in a single `Write` transaction.
+///
+/// A `Write` transaction without the data is about 200 bytes, the rest of
+/// [`PACKET_DATA_SIZE`](solana_sdk::packet::PACKET_DATA_SIZE)
+/// leaves room for the compute budget instructions of the priority fee.
+const IDL_MAX_WRITE_SIZE: usize = 900;
+
+/// Create the IDL account of the program and write the compressed IDL to it.
+///
+/// IDLs that don't fit in a single transaction are written in chunks of
+/// [`IDL_MAX_WRITE_SIZE`] bytes, one transaction per chunk. Returns the signature of the last
+/// transaction.
+pub async fn publish_idl(
+    authority: &dyn signer::PgSigner,
+    program_id: &Pubkey,
+    idl: &Idl,
+) -> CliResult<Signature> {
+    let idl_address = IdlAccount::address(program_id);
+    let client = get_client();
+    if client
+        .get_account_with_commitment(&idl_address, client.commitment_config())
+        .await?
+        .is_some()
+    {
+        return Err(anyhow!(
+            "IDL account {idl_address} already exists, use `anchor idl upgrade` instead"
+        ));
+    }
+
+    let idl_data = serialize_idl(idl)?;
+
+    // `Create` can only allocate up to `IDL_MAX_ALLOCATION` bytes, the rest is allocated with
+    // `Resize` instructions
+    let data_len = idl_account_data_len(idl_data.len())?;
+    let program_signer = Pubkey::find_program_address(&[], program_id).0;
+    let mut ixs = vec![Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(idl_address, false),
+            AccountMeta::new_readonly(program_signer, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(*program_id, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+        data: serialize_idl_ix(IdlInstruction::Create { data_len })?,
+    }];
+    for _ in 0..data_len / IDL_MAX_ALLOCATION {
+        ixs.push(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(idl_address, false),
+                AccountMeta::new_readonly(authority.pubkey(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: serialize_idl_ix(IdlInstruction::Resize { data_len })?,
+        });
     }
+    let signature = send_and_confirm(authority, &ixs, &[]).await?;
+
+    Ok(write_idl(authority, program_id, &idl_address, &idl_data)
+        .await?
+        .unwrap_or(signature))
+}
+
+/// Write the compressed IDL to the IDL account or to an IDL buffer, one transaction per chunk.
+///
+/// Returns the signature of the last transaction, `None` if there was no data to write.
+pub async fn write_idl(
+    authority: &dyn signer::PgSigner,
+    program_id: &Pubkey,
+    idl_address: &Pubkey,
+    idl_data: &[u8],
+) -> CliResult<Option<Signature>> {
+    let mut signature = None;
+    for chunk in split_idl_data(idl_data) {
+        let ix = idl_write_instruction(program_id, idl_address, &authority.pubkey(), chunk)?;
+        signature = Some(send_and_confirm(authority, &[ix], &[]).await?);
+    }
+
+    Ok(signature)
+}
+
+fn idl_write_instruction(
+    program_id: &Pubkey,
+    idl_address: &Pubkey,
+    authority: &Pubkey,
+    chunk: &[u8],
+) -> CliResult<Instruction> {
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*idl_address, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data: serialize_idl_ix(IdlInstruction::Write {
+            data: chunk.to_vec(),
+        })?,
+    })
+}
+
+/// Serialize and compress the IDL the way it's stored on-chain, without the metadata
+pub fn serialize_idl(idl: &Idl) -> CliResult<Vec<u8>> {
+    let mut idl = idl.clone();
+    idl.metadata = None;
+
+    let json_bytes = serde_json::to_vec(&idl)?;
+    let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
+    e.write_all(&json_bytes)?;
+    Ok(e.finish()?)
+}
+
+/// Get the IDL account data length to allocate for the compressed IDL, doubled for future growth
+fn idl_account_data_len(idl_data_len: usize) -> CliResult<u64> {
+    let idl_data_len = idl_data_len as u64;
+    if idl_data_len > IDL_MAX_DATA_LEN - IDL_HEADER_SIZE {
+        return Err(anyhow!(
+            "IDL is {idl_data_len} bytes after compression, maximum is {} bytes",
+            IDL_MAX_DATA_LEN - IDL_HEADER_SIZE
+        ));
+    }
+
+    Ok((idl_data_len * 2).min(IDL_MAX_DATA_LEN - IDL_HEADER_SIZE))
+}
+
+/// Split the compressed IDL into chunks that each fit in a `Write` transaction
+fn split_idl_data(idl_data: &[u8]) -> Vec<&[u8]> {
+    idl_data.chunks(IDL_MAX_WRITE_SIZE).collect()
+}
+
+/// Serialize the IDL instruction along with the IDL instruction tag
+pub fn serialize_idl_ix(ix: IdlInstruction) -> CliResult<Vec<u8>> {
+    let mut data = IDL_IX_TAG.to_le_bytes().to_vec();
+    data.append(&mut ix.try_to_vec()?);
+    Ok(data)
+}
+
+thread_local! {
+    /// Last parsed IDL along with the string it was parsed from
+    static IDL_CACHE: RefCell<Option<(String, Idl)>> = RefCell::new(None);
+}
+
+/// Parse the IDL, reusing the last result if the IDL string hasn't changed
+fn parse_idl_cached(idl_string: String) -> CliResult<Idl> {
+    IDL_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some((cached_idl_string, idl)) = cache.as_ref() {
+            if *cached_idl_string == idl_string {
+                return Ok(idl.clone());
+            }
+        }
+
+        let idl = parse_idl(&idl_string)?;
+        *cache = Some((idl_string, idl.clone()));
+        Ok(idl)
+    })
 }
 
 pub fn get_program_id(maybe_program_id: Option<Pubkey>) -> CliResult<Pubkey> {
     match maybe_program_id {
         Some(program_id) => Ok(program_id),
         None => match PgProgramInfo::pk_string() {
-            Some(program_id_string) => Ok(Pubkey::from_str(&program_id_string).unwrap()),
-            None => Err(anyhow!("Program id doesn't exist")),
+            Some(program_id_string) => parse_program_id(&program_id_string),
+            None => Err(PgError::NoProgramId.into()),
         },
     }
 }
+
+/// Check whether the program is deployed, i.e. the account exists and is executable
+pub async fn ensure_program_deployed(program_id: &Pubkey) -> CliResult {
+    let client = get_client();
+    let account = client
+        .get_account_with_commitment(program_id, client.commitment_config())
+        .await?;
+
+    check_program_account(program_id, account.as_ref())
+}
+
+fn check_program_account(program_id: &Pubkey, account: Option<&Account>) -> CliResult {
+    match account {
+        Some(account) if account.executable => Ok(()),
+        _ => Err(PgError::NotDeployed(*program_id).into()),
+    }
+}
+
+fn parse_program_id(program_id_string: &str) -> CliResult<Pubkey> {
+    Pubkey::from_str(program_id_string).map_err(|e| {
+        PgError::InvalidProgramId {
+            value: program_id_string.to_owned(),
+            reason: e.to_string(),
+        }
+        .into()
+    })
+}
+
+pub async fn get_balance(pubkey: &Pubkey) -> CliResult<u64> {
+    Ok(get_client().get_balance(pubkey).await?)
+}
+
+/// Get the balance in SOL. The result is not rounded, formatting is left to the caller.
+pub async fn get_balance_sol(pubkey: &Pubkey) -> CliResult<f64> {
+    Ok(lamports_to_sol(get_balance(pubkey).await?))
+}
+
+/// Make sure the wallet has at least `min_lamports`, e.g. the estimated fee of a transaction,
+/// before sending transactions with it
+pub async fn ensure_funded(pubkey: &Pubkey, min_lamports: u64) -> CliResult {
+    let balance = get_balance(pubkey).await?;
+    check_funded(pubkey, balance, min_lamports)
+}
+
+fn check_funded(pubkey: &Pubkey, balance: u64, min_lamports: u64) -> CliResult {
+    if balance < min_lamports {
+        return Err(anyhow!(
+            "Wallet {pubkey} has {balance} lamports, needs at least {min_lamports}. Try an airdrop."
+        ));
+    }
+
+    Ok(())
+}
+
+/// Get the fee of the message in lamports.
+///
+/// Errors if the blockhash of the message has expired since the fee can't be calculated.
+pub async fn estimate_fee(message: &Message) -> CliResult<u64> {
+    let client = get_client();
+
+    // RPC returns `null` for expired blockhashes which the client converts to `0`
+    let is_valid = client
+        .is_blockhash_valid(&message.recent_blockhash, client.commitment_config())
+        .await?;
+    if !is_valid {
+        return Err(PgError::BlockhashExpired(message.recent_blockhash).into());
+    }
+
+    Ok(client.get_fee_for_message(message).await?)
+}
+
+/// Get the fee of the message in SOL
+pub async fn estimate_fee_sol(message: &Message) -> CliResult<f64> {
+    Ok(lamports_to_sol(estimate_fee(message).await?))
+}
+
+fn lamports_to_sol(lamports: u64) -> f64 {
+    lamports as f64 / LAMPORTS_PER_SOL as f64
+}
+
+pub fn get_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address(wallet, mint)
+}
+
+/// Get the token amount of the wallet's associated token account, `0` if it doesn't exist
+pub async fn get_token_balance(wallet: &Pubkey, mint: &Pubkey) -> CliResult<u64> {
+    let client = get_client();
+    let token_address = get_associated_token_address(wallet, mint);
+    match client
+        .get_account_with_commitment(&token_address, client.commitment_config())
+        .await?
+    {
+        Some(account) => {
+            let token_account = spl_token::state::Account::unpack(&account.data)
+                .map_err(|e| anyhow!("Invalid token account {token_address}: {e}"))?;
+            Ok(token_account.amount)
+        }
+        None => Ok(0),
+    }
+}
+
+/// Transfer tokens from the payer's associated token account to the wallet's associated token
+/// account, creating the destination account if it doesn't exist
+pub async fn transfer_tokens(
+    payer: &dyn signer::PgSigner,
+    mint: &Pubkey,
+    to_wallet: &Pubkey,
+    amount: u64,
+) -> CliResult<Signature> {
+    let payer_pubkey = payer.pubkey();
+    let source = get_associated_token_address(&payer_pubkey, mint);
+    let destination = get_associated_token_address(to_wallet, mint);
+
+    let mut accounts = get_client()
+        .get_multiple_accounts(&[*mint, source, destination])
+        .await?
+        .into_iter();
+    let mint_account = accounts
+        .next()
+        .flatten()
+        .ok_or_else(|| anyhow!("Mint {mint} not found"))?;
+    let source_account = accounts.next().flatten();
+    let destination_exists = accounts.next().flatten().is_some();
+
+    let decimals = spl_token::state::Mint::unpack(&mint_account.data)
+        .map_err(|e| anyhow!("Invalid mint {mint}: {e}"))?
+        .decimals;
+    let source_balance = match source_account {
+        Some(account) => {
+            spl_token::state::Account::unpack(&account.data)
+                .map_err(|e| anyhow!("Invalid token account {source}: {e}"))?
+                .amount
+        }
+        None => 0,
+    };
+    if source_balance < amount {
+        return Err(anyhow!(
+            "Insufficient token balance in {source}: {source_balance}, trying to transfer {amount}"
+        ));
+    }
+
+    let ixs = transfer_tokens_instructions(
+        &payer_pubkey,
+        mint,
+        to_wallet,
+        amount,
+        decimals,
+        destination_exists,
+    )?;
+    send_and_confirm(payer, &ixs, &[]).await
+}
+
+fn transfer_tokens_instructions(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    to_wallet: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    destination_exists: bool,
+) -> CliResult<Vec<Instruction>> {
+    let mut ixs = vec![];
+    if !destination_exists {
+        ixs.push(
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                payer,
+                to_wallet,
+                mint,
+                &spl_token::id(),
+            ),
+        );
+    }
+
+    ixs.push(
+        spl_token::instruction::transfer_checked(
+            &spl_token::id(),
+            &get_associated_token_address(payer, mint),
+            mint,
+            &get_associated_token_address(to_wallet, mint),
+            payer,
+            &[],
+            amount,
+            decimals,
+        )
+        .map_err(|e| anyhow!("Failed to create the transfer instruction: {e}"))?,
+    );
+
+    Ok(ixs)
+}
+
+/// Fetch the accounts in batches, preserving the order of the addresses. Missing accounts are
+/// returned as `None`.
+pub async fn get_accounts(addresses: &[Pubkey]) -> CliResult<Vec<Option<Account>>> {
+    let client = get_client();
+    get_in_chunks(addresses, MAX_MULTIPLE_ACCOUNTS, |chunk| {
+        client.get_multiple_accounts(chunk)
+    })
+    .await
+}
+
+/// Split the items into chunks and make a request for each chunk, preserving the order
+async fn get_in_chunks<'a, I, T, F, Fut>(
+    items: &'a [I],
+    chunk_size: usize,
+    mut f: F,
+) -> CliResult<Vec<T>>
+where
+    F: FnMut(&'a [I]) -> Fut,
+    Fut: Future<Output = ClientResult<Vec<T>>>,
+{
+    let mut results = Vec::with_capacity(items.len());
+    for chunk in items.chunks(chunk_size) {
+        let chunk_results = f(chunk).await?;
+        if chunk_results.len() != chunk.len() {
+            return Err(anyhow!(
+                "Expected {} results, received {}",
+                chunk.len(),
+                chunk_results.len()
+            ));
+        }
+        results.extend(chunk_results);
+    }
+
+    Ok(results)
+}
+
+/// Get the accounts owned by the program that match all of the filters.
+///
+/// NOTE: Public RPC endpoints might reject unfiltered requests for programs with many accounts,
+/// [`discriminator_filter`] and `DataSize` filters can be used to narrow the results.
+pub async fn get_program_accounts(
+    program_id: &Pubkey,
+    filters: Vec<RpcFilterType>,
+) -> CliResult<Vec<(Pubkey, Account)>> {
+    for filter in &filters {
+        filter
+            .verify()
+            .map_err(|e| anyhow!("Invalid filter {filter:?}: {e}"))?;
+    }
+
+    let is_filtered = !filters.is_empty();
+    get_client()
+        .get_program_accounts_with_config(
+            program_id,
+            RpcProgramAccountsConfig {
+                filters: is_filtered.then_some(filters),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| match is_filtered {
+            true => anyhow!("Failed to get the accounts of {program_id}: {e}"),
+            false => anyhow!(
+                "Failed to get the accounts of {program_id}: {e}. \
+                Unfiltered requests are often rejected by public RPC endpoints, \
+                try narrowing the results with filters."
+            ),
+        })
+}
+
+/// Filter to only get the Anchor accounts with the given account name
+pub fn discriminator_filter(account_name: &str) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp {
+        offset: 0,
+        bytes: MemcmpEncodedBytes::Base64(base64::encode(account_discriminator(account_name))),
+        encoding: None,
+    })
+}
+
+pub async fn fetch_account<T: AccountDeserialize>(address: &Pubkey) -> CliResult<T> {
+    let data = get_client().get_account_data(address).await?;
+    T::try_deserialize(&mut data.as_slice())
+        .map_err(|e| anyhow!("Failed to deserialize account {address}: {e}"))
+}
+
+/// Fetch the account and decode its data into JSON based on the IDL account definition
+pub async fn fetch_account_json(
+    address: &Pubkey,
+    account_name: &str,
+) -> CliResult<serde_json::Value> {
+    let idl = get_idl()?;
+    let account_def = idl
+        .accounts
+        .iter()
+        .find(|account_def| account_def.name == account_name)
+        .ok_or_else(|| anyhow!("Account '{account_name}' not found in the IDL"))?;
+
+    let data = get_client().get_account_data(address).await?;
+    if !verify_discriminator(&data, &account_discriminator(account_name)) {
+        return Err(anyhow!(
+            "Account {address} is not a '{account_name}' account: discriminator mismatch"
+        ));
+    }
+
+    // Cut off account discriminator
+    let mut data = &data[8..];
+    coder::decode_type_definition(&idl, &account_def.ty, &mut data)
+}
+
+/// Encodings to display the account data with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Base58,
+    Base64,
+    Hex,
+}
+
+pub fn encode_account_data(data: &[u8], encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Base58 => bs58::encode(data).into_string(),
+        Encoding::Base64 => base64::encode(data),
+        Encoding::Hex => hex::encode(data),
+    }
+}
+
+pub fn decode_account_data(data: &str, encoding: Encoding) -> CliResult<Vec<u8>> {
+    match encoding {
+        Encoding::Base58 => bs58::decode(data)
+            .into_vec()
+            .map_err(|e| anyhow!("Invalid base58 data: {e}")),
+        Encoding::Base64 => base64::decode(data).map_err(|e| anyhow!("Invalid base64 data: {e}")),
+        Encoding::Hex => hex::decode(data).map_err(|e| anyhow!("Invalid hex data: {e}")),
+    }
+}
+
+/// Build an instruction from the IDL definition.
+///
+/// `args` must be a JSON object keyed by the IDL argument names and `accounts` must include all
+/// the accounts of the instruction by their IDL names.
+pub fn build_instruction(
+    idl: &Idl,
+    name: &str,
+    args: serde_json::Value,
+    accounts: &[(String, Pubkey)],
+) -> CliResult<Instruction> {
+    let idl_ix = idl
+        .instructions
+        .iter()
+        .find(|idl_ix| idl_ix.name == name)
+        .ok_or_else(|| anyhow!("Instruction '{name}' not found in the IDL"))?;
+
+    let mut data = instruction_discriminator(name).to_vec();
+    coder::encode_fields(idl, &idl_ix.args, &args, &mut data)
+        .map_err(|e| anyhow!("Invalid arguments for '{name}': {e}"))?;
+
+    let program_id = get_idl_program_id(idl)?;
+
+    // PDA accounts that are not given are resolved from their seeds
+    let mut accounts = accounts.to_vec();
+    let mut metas = vec![];
+    for idl_account in flatten_accounts(&idl_ix.accounts) {
+        let pubkey = match (find_account(&accounts, &idl_account.name), &idl_account.pda) {
+            (Some(pubkey), _) => pubkey,
+            (None, Some(pda)) => {
+                let pubkey = resolve_pda(idl, pda, &args, &accounts, &program_id).map_err(|e| {
+                    anyhow!("Failed to resolve account '{}': {e}", idl_account.name)
+                })?;
+                accounts.push((idl_account.name.clone(), pubkey));
+                pubkey
+            }
+            (None, None) => {
+                return Err(anyhow!(
+                    "Missing account '{}' for instruction '{name}'",
+                    idl_account.name
+                ))
+            }
+        };
+        metas.push(if idl_account.is_mut {
+            AccountMeta::new(pubkey, idl_account.is_signer)
+        } else {
+            AccountMeta::new_readonly(pubkey, idl_account.is_signer)
+        });
+    }
+
+    Ok(Instruction {
+        program_id,
+        accounts: metas,
+        data,
+    })
+}
+
+/// Decode the instruction data into the IDL instruction name and its arguments as JSON.
+///
+/// This is the reverse of [`build_instruction`].
+pub fn decode_instruction(idl: &Idl, data: &[u8]) -> CliResult<(String, serde_json::Value)> {
+    let discriminator = data
+        .get(..8)
+        .ok_or_else(|| anyhow!("Instruction data is too short: {} bytes", data.len()))?;
+    let idl_ix = idl
+        .instructions
+        .iter()
+        .find(|idl_ix| instruction_discriminator(&idl_ix.name) == discriminator)
+        .ok_or_else(|| {
+            anyhow!(
+                "Unknown instruction discriminator: {}",
+                hex::encode(discriminator)
+            )
+        })?;
+
+    let mut data = &data[8..];
+    let args = coder::decode_fields(idl, &idl_ix.args, &mut data)
+        .map_err(|e| anyhow!("Invalid arguments for '{}': {e}", idl_ix.name))?;
+    Ok((idl_ix.name.clone(), args))
+}
+
+fn find_account(accounts: &[(String, Pubkey)], name: &str) -> Option<Pubkey> {
+    accounts
+        .iter()
+        .find(|(account_name, _)| account_name == name)
+        .map(|(_, pubkey)| *pubkey)
+}
+
+/// Seed of a program derived address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Seed {
+    Bytes(Vec<u8>),
+    Str(String),
+    Pubkey(Pubkey),
+    /// Little-endian encoded
+    U64(u64),
+}
+
+impl Seed {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Bytes(bytes) => bytes.clone(),
+            Self::Str(s) => s.as_bytes().to_vec(),
+            Self::Pubkey(pubkey) => pubkey.to_bytes().to_vec(),
+            Self::U64(n) => n.to_le_bytes().to_vec(),
+        }
+    }
+}
+
+/// Find the program derived address and its bump seed
+pub fn find_pda(program_id: &Pubkey, seeds: &[Seed]) -> (Pubkey, u8) {
+    let seeds = seeds.iter().map(|seed| seed.to_bytes()).collect::<Vec<_>>();
+    let seeds = seeds.iter().map(|seed| seed.as_slice()).collect::<Vec<_>>();
+    Pubkey::find_program_address(&seeds, program_id)
+}
+
+/// Resolve the PDA from its IDL seeds.
+///
+/// Seeds can reference the instruction arguments and the accounts that are already known.
+fn resolve_pda(
+    idl: &Idl,
+    pda: &IdlPda,
+    args: &serde_json::Value,
+    accounts: &[(String, Pubkey)],
+    program_id: &Pubkey,
+) -> CliResult<Pubkey> {
+    let seeds = pda
+        .seeds
+        .iter()
+        .map(|seed| resolve_seed(idl, seed, args, accounts))
+        .collect::<CliResult<Vec<_>>>()?;
+    let program_id = match &pda.program_id {
+        Some(seed) => {
+            let bytes = resolve_seed(idl, seed, args, accounts)?.to_bytes();
+            Pubkey::try_from(bytes.as_slice())
+                .map_err(|_| anyhow!("Invalid PDA program id: {bytes:?}"))?
+        }
+        None => *program_id,
+    };
+
+    Ok(find_pda(&program_id, &seeds).0)
+}
+
+fn resolve_seed(
+    idl: &Idl,
+    seed: &IdlSeed,
+    args: &serde_json::Value,
+    accounts: &[(String, Pubkey)],
+) -> CliResult<Seed> {
+    match seed {
+        IdlSeed::Const(seed) => seed_from_value(idl, &seed.ty, &seed.value),
+        IdlSeed::Arg(seed) => {
+            let value = seed
+                .path
+                .split('.')
+                .try_fold(args, |value, key| value.get(key))
+                .ok_or_else(|| anyhow!("Missing argument '{}'", seed.path))?;
+            seed_from_value(idl, &seed.ty, value)
+        }
+        IdlSeed::Account(seed) => {
+            if seed.path.contains('.') {
+                return Err(anyhow!(
+                    "Seeds from account data are not supported: '{}'",
+                    seed.path
+                ));
+            }
+
+            find_account(accounts, &seed.path)
+                .map(Seed::Pubkey)
+                .ok_or_else(|| anyhow!("Missing account '{}'", seed.path))
+        }
+    }
+}
+
+fn seed_from_value(idl: &Idl, ty: &IdlType, value: &serde_json::Value) -> CliResult<Seed> {
+    match ty {
+        IdlType::String => value
+            .as_str()
+            .map(|s| Seed::Str(s.to_owned()))
+            .ok_or_else(|| anyhow!("Expected a string seed, found {value}")),
+        IdlType::Bytes => serde_json::from_value(value.clone())
+            .map(Seed::Bytes)
+            .map_err(|e| anyhow!("Invalid bytes seed: {e}")),
+        // The rest of the types are used with their Borsh encoding without a length prefix
+        _ => {
+            let mut bytes = vec![];
+            coder::encode_type(idl, ty, value, &mut bytes)?;
+            Ok(Seed::Bytes(bytes))
+        }
+    }
+}
+
+/// Flatten the nested account groups of the instruction in their declaration order
+fn flatten_accounts(items: &[IdlAccountItem]) -> Vec<&anchor_syn::idl::IdlAccount> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            IdlAccountItem::IdlAccount(account) => vec![account],
+            IdlAccountItem::IdlAccounts(accounts) => flatten_accounts(&accounts.accounts),
+        })
+        .collect()
+}
+
+/// Get the program id from the IDL metadata, or the current program id if it doesn't exist
+fn get_idl_program_id(idl: &Idl) -> CliResult<Pubkey> {
+    match idl
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("address"))
+        .and_then(|address| address.as_str())
+    {
+        Some(address) => parse_program_id(address),
+        None => get_program_id(None),
+    }
+}
+
+/// Convert the camelCase IDL name to the snake_case Rust name
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake_case = String::with_capacity(name.len());
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i != 0 {
+            let prev = chars[i - 1];
+            let next_is_lowercase = chars.get(i + 1).map_or(false, |next| next.is_lowercase());
+            if !prev.is_uppercase() || next_is_lowercase {
+                snake_case.push('_');
+            }
+        }
+        snake_case.extend(c.to_lowercase());
+    }
+
+    snake_case
+}
+
+/// Decode the Anchor events emitted in the transaction logs.
+///
+/// Events are logged as base64 encoded `Program data:` entries. Entries that don't match any of
+/// the IDL events are skipped.
+pub fn parse_events(idl: &Idl, logs: &[String]) -> Vec<(String, serde_json::Value)> {
+    const PROGRAM_DATA: &str = "Program data: ";
+
+    let events = match &idl.events {
+        Some(events) => events,
+        None => return vec![],
+    };
+
+    logs.iter()
+        .filter_map(|log| log.strip_prefix(PROGRAM_DATA))
+        .filter_map(|data| base64::decode(data).ok())
+        .filter_map(|data| {
+            let event = events
+                .iter()
+                .find(|event| verify_discriminator(&data, &event_discriminator(&event.name)))?;
+
+            let mut data = &data[8..];
+            let mut fields = serde_json::Map::new();
+            for field in &event.fields {
+                let value = coder::decode_type(idl, &field.ty, &mut data).ok()?;
+                fields.insert(field.name.clone(), value);
+            }
+
+            Some((event.name.clone(), serde_json::Value::Object(fields)))
+        })
+        .collect()
+}
+
+/// All of the Anchor framework errors
+const ANCHOR_ERRORS: &[ErrorCode] = &[
+    // Instructions
+    ErrorCode::InstructionMissing,
+    ErrorCode::InstructionFallbackNotFound,
+    ErrorCode::InstructionDidNotDeserialize,
+    ErrorCode::InstructionDidNotSerialize,
+    // IDL instructions
+    ErrorCode::IdlInstructionStub,
+    ErrorCode::IdlInstructionInvalidProgram,
+    // Constraints
+    ErrorCode::ConstraintMut,
+    ErrorCode::ConstraintHasOne,
+    ErrorCode::ConstraintSigner,
+    ErrorCode::ConstraintRaw,
+    ErrorCode::ConstraintOwner,
+    ErrorCode::ConstraintRentExempt,
+    ErrorCode::ConstraintSeeds,
+    ErrorCode::ConstraintExecutable,
+    ErrorCode::ConstraintState,
+    ErrorCode::ConstraintAssociated,
+    ErrorCode::ConstraintAssociatedInit,
+    ErrorCode::ConstraintClose,
+    ErrorCode::ConstraintAddress,
+    ErrorCode::ConstraintZero,
+    ErrorCode::ConstraintTokenMint,
+    ErrorCode::ConstraintTokenOwner,
+    ErrorCode::ConstraintMintMintAuthority,
+    ErrorCode::ConstraintMintFreezeAuthority,
+    ErrorCode::ConstraintMintDecimals,
+    ErrorCode::ConstraintSpace,
+    ErrorCode::ConstraintAccountIsNone,
+    // Require
+    ErrorCode::RequireViolated,
+    ErrorCode::RequireEqViolated,
+    ErrorCode::RequireKeysEqViolated,
+    ErrorCode::RequireNeqViolated,
+    ErrorCode::RequireKeysNeqViolated,
+    ErrorCode::RequireGtViolated,
+    ErrorCode::RequireGteViolated,
+    // Accounts
+    ErrorCode::AccountDiscriminatorAlreadySet,
+    ErrorCode::AccountDiscriminatorNotFound,
+    ErrorCode::AccountDiscriminatorMismatch,
+    ErrorCode::AccountDidNotDeserialize,
+    ErrorCode::AccountDidNotSerialize,
+    ErrorCode::AccountNotEnoughKeys,
+    ErrorCode::AccountNotMutable,
+    ErrorCode::AccountOwnedByWrongProgram,
+    ErrorCode::InvalidProgramId,
+    ErrorCode::InvalidProgramExecutable,
+    ErrorCode::AccountNotSigner,
+    ErrorCode::AccountNotSystemOwned,
+    ErrorCode::AccountNotInitialized,
+    ErrorCode::AccountNotProgramData,
+    ErrorCode::AccountNotAssociatedTokenAccount,
+    ErrorCode::AccountSysvarMismatch,
+    ErrorCode::AccountReallocExceedsLimit,
+    ErrorCode::AccountDuplicateReallocs,
+    // State
+    ErrorCode::StateInvalidAddress,
+    // Miscellaneous
+    ErrorCode::DeclaredProgramIdMismatch,
+    // Deprecated APIs
+    ErrorCode::Deprecated,
+];
+
+/// Get a human readable message for the custom program error of the failed transaction.
+///
+/// The error code is looked up in the IDL errors first, then in the Anchor framework errors.
+pub fn explain_error(idl: &Idl, err: &ClientError) -> String {
+    const CUSTOM_PROGRAM_ERROR: &str = "custom program error: 0x";
+
+    let code = err
+        .message()
+        .split(CUSTOM_PROGRAM_ERROR)
+        .nth(1)
+        .and_then(|hex| {
+            let hex_len = hex
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(hex.len());
+            u32::from_str_radix(&hex[..hex_len], 16).ok()
+        });
+    let code = match code {
+        Some(code) => code,
+        None => return err.to_string(),
+    };
+
+    let idl_error = idl
+        .errors
+        .iter()
+        .flatten()
+        .find(|idl_error| idl_error.code == code);
+    if let Some(idl_error) = idl_error {
+        return idl_error
+            .msg
+            .clone()
+            .unwrap_or_else(|| idl_error.name.clone());
+    }
+
+    match ANCHOR_ERRORS
+        .iter()
+        .find(|error_code| u32::from(**error_code) == code)
+    {
+        Some(error_code) => error_code.to_string(),
+        None => format!("{CUSTOM_PROGRAM_ERROR}{code:x}"),
+    }
+}
+
+/// Anchor instruction discriminator, first 8 bytes of `sha256("global:<snake_case_name>")`.
+///
+/// camelCase IDL names are converted to snake_case.
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    discriminator(&format!("global:{}", to_snake_case(name)))
+}
+
+/// Anchor account discriminator, first 8 bytes of `sha256("account:<name>")`
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    discriminator(&format!("account:{name}"))
+}
+
+/// Anchor event discriminator, first 8 bytes of `sha256("event:<name>")`
+pub fn event_discriminator(name: &str) -> [u8; 8] {
+    discriminator(&format!("event:{name}"))
+}
+
+/// Check whether the data starts with the expected discriminator
+pub fn verify_discriminator(data: &[u8], expected: &[u8; 8]) -> bool {
+    data.starts_with(expected)
+}
+
+fn discriminator(preimage: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(preimage.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Initial delay between retries, doubled after every attempt
+const RETRY_BASE_DELAY_MS: u64 = 500;
+/// Maximum delay between retries
+const RETRY_MAX_DELAY_MS: u64 = 8_000;
+
+/// Retry the RPC request on rate limit or connection errors with exponential backoff
+pub async fn with_retry<F, Fut, T>(f: F) -> CliResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    with_retry_count(PgConnection::retry_count(), f).await
+}
+
+async fn with_retry_count<F, Fut, T>(retry_count: u32, mut f: F) -> CliResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < retry_count && is_retryable(&e) => {
+                sleep(retry_delay_ms(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn retry_delay_ms(attempt: u32) -> u64 {
+    RETRY_BASE_DELAY_MS
+        .saturating_mul(2u64.saturating_pow(attempt))
+        .min(RETRY_MAX_DELAY_MS)
+}
+
+/// Get whether the error is caused by rate limiting or connection problems
+fn is_retryable(err: &ClientError) -> bool {
+    matches!(err.code(), 429 | 500..=599)
+}
+
+/// Simulate the transaction without sending it.
+///
+/// Simulation errors are returned with the program logs attached.
+pub async fn simulate(tx: &Transaction) -> CliResult<RpcSimulateTransactionResult> {
+    let result = get_client().simulate_transaction(tx).await?;
+    match &result.err {
+        Some(err) => Err(simulation_error(
+            err,
+            result.logs.as_deref().unwrap_or_default(),
+        )),
+        None => Ok(result),
+    }
+}
+
+/// Build, sign and simulate a legacy transaction the same way [`send_and_confirm`] sends it
+pub async fn simulate_instructions(
+    payer: &dyn signer::PgSigner,
+    ixs: &[Instruction],
+    extra_signers: &[&dyn signer::PgSigner],
+) -> CliResult<RpcSimulateTransactionResult> {
+    let mut ixs = ixs.to_vec();
+    apply_priority_fee(&mut ixs);
+    let tx = sign_transaction(payer, &ixs, extra_signers, get_cached_blockhash().await?).await?;
+    simulate(&tx).await
+}
+
+/// Log the program logs and the consumed compute units of the simulation to the terminal
+pub fn log_simulation_result(result: &RpcSimulateTransactionResult) {
+    if let Some(logs) = &result.logs {
+        PgTerminal::log_wasm(&logs.join("\n"));
+    }
+    if let Some(units_consumed) = result.units_consumed {
+        PgTerminal::log_wasm(&format!("Compute units consumed: {units_consumed}"));
+    }
+}
+
+fn simulation_error(err: &TransactionError, logs: &[String]) -> anyhow::Error {
+    anyhow!("Simulation failed: {err}\nLogs:\n{}", logs.join("\n"))
+}
+
+/// Prepend the compute budget instructions to set the priority fee and optionally the compute
+/// unit limit of the transaction
+pub fn with_priority_fee(
+    ixs: &mut Vec<Instruction>,
+    micro_lamports: u64,
+    compute_unit_limit: Option<u32>,
+) {
+    let mut compute_budget_ixs = Vec::with_capacity(2);
+    if let Some(compute_unit_limit) = compute_unit_limit {
+        compute_budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(
+            compute_unit_limit,
+        ));
+    }
+    compute_budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+        micro_lamports,
+    ));
+
+    ixs.splice(0..0, compute_budget_ixs);
+}
+
+/// Attach the priority fee from the connection settings, if any.
+///
+/// Instructions that already include compute budget instructions are left as is.
+pub fn apply_priority_fee(ixs: &mut Vec<Instruction>) {
+    attach_priority_fee(ixs, PgConnection::priority_fee());
+}
+
+fn attach_priority_fee(ixs: &mut Vec<Instruction>, micro_lamports: u64) {
+    let has_compute_budget = ixs.iter().any(|ix| ix.program_id == compute_budget::id());
+    if micro_lamports != 0 && !has_compute_budget {
+        with_priority_fee(ixs, micro_lamports, None);
+    }
+}
+
+/// How long the cached blockhash is used before fetching a new one
+const BLOCKHASH_TTL_MS: f64 = 20_000.0;
+
+/// Blockhash that was fetched from the endpoint
+struct CachedBlockhash {
+    endpoint: String,
+    hash: Hash,
+    last_valid_block_height: u64,
+    /// Time the blockhash was fetched at in milliseconds
+    fetched_at: f64,
+}
+
+thread_local! {
+    /// Last fetched blockhash
+    static BLOCKHASH_CACHE: RefCell<Option<CachedBlockhash>> = RefCell::new(None);
+}
+
+/// Get the latest blockhash along with the last block height that it's valid at
+pub async fn get_blockhash_with_expiry() -> CliResult<(Hash, u64)> {
+    let client = get_client();
+    Ok(client
+        .get_latest_blockhash_with_config(client.commitment_config())
+        .await?)
+}
+
+/// Get the latest blockhash, reusing the last fetched blockhash if it's younger than
+/// [`BLOCKHASH_TTL_MS`] and it was fetched from the current endpoint
+pub async fn get_cached_blockhash() -> CliResult<Hash> {
+    let (hash, _) = get_cached_blockhash_with_expiry().await?;
+    Ok(hash)
+}
+
+/// Remove the cached blockhash so that the next call fetches a new one
+fn invalidate_blockhash_cache() {
+    BLOCKHASH_CACHE.with(|cache| cache.borrow_mut().take());
+}
+
+/// Same as [`get_cached_blockhash`] but also returns the last valid block height
+async fn get_cached_blockhash_with_expiry() -> CliResult<(Hash, u64)> {
+    get_cached_blockhash_with_clock(
+        &PgConnection::endpoint(),
+        js_sys::Date::now,
+        get_blockhash_with_expiry,
+    )
+    .await
+}
+
+async fn get_cached_blockhash_with_clock<C, F, Fut>(
+    endpoint: &str,
+    now: C,
+    fetch: F,
+) -> CliResult<(Hash, u64)>
+where
+    C: Fn() -> f64,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = CliResult<(Hash, u64)>>,
+{
+    let cached = BLOCKHASH_CACHE.with(|cache| match &*cache.borrow() {
+        Some(cached)
+            if cached.endpoint == endpoint && now() - cached.fetched_at < BLOCKHASH_TTL_MS =>
+        {
+            Some((cached.hash, cached.last_valid_block_height))
+        }
+        _ => None,
+    });
+    if let Some(cached) = cached {
+        return Ok(cached);
+    }
+
+    let (hash, last_valid_block_height) = fetch().await?;
+    BLOCKHASH_CACHE.with(|cache| {
+        *cache.borrow_mut() = Some(CachedBlockhash {
+            endpoint: endpoint.to_owned(),
+            hash,
+            last_valid_block_height,
+            fetched_at: now(),
+        })
+    });
+    Ok((hash, last_valid_block_height))
+}
+
+/// Address lookup table program id
+const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey =
+    pubkey!("AddressLookupTab1e1111111111111111111111111");
+/// Size of the lookup table metadata that precedes the addresses
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+
+/// Build, sign and send a v0 transaction that can make use of address lookup tables
+pub async fn send_versioned(
+    payer: &dyn signer::PgSigner,
+    ixs: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> CliResult<Signature> {
+    let client = get_client();
+    let latest_hash = get_cached_blockhash().await?;
+
+    let mut ixs = ixs.to_vec();
+    apply_priority_fee(&mut ixs);
+    let message = v0::Message::try_compile(&payer.pubkey(), &ixs, lookup_tables, latest_hash)
+        .map_err(|e| anyhow!("Failed to compile the transaction message: {e}"))?;
+    let message = VersionedMessage::V0(message);
+    if message.header().num_required_signatures != 1 {
+        return Err(anyhow!(
+            "Failed to sign the transaction: only the payer signature is supported"
+        ));
+    }
+
+    let signature = payer.sign_transaction_message(&message.serialize()).await?;
+    let tx = VersionedTransaction {
+        signatures: vec![signature],
+        message,
+    };
+
+    Ok(client.send_versioned_transaction(&tx).await?)
+}
+
+/// Fetch the address lookup table account so that it can be used in v0 transactions
+pub async fn fetch_lookup_table(address: &Pubkey) -> CliResult<AddressLookupTableAccount> {
+    let client = get_client();
+    let account = client
+        .get_account_with_commitment(address, client.commitment_config())
+        .await?
+        .ok_or_else(|| anyhow!("Address lookup table {address} not found"))?;
+    if account.owner != ADDRESS_LOOKUP_TABLE_PROGRAM_ID {
+        return Err(anyhow!("{address} is not an address lookup table"));
+    }
+
+    parse_lookup_table(address, &account.data)
+}
+
+fn parse_lookup_table(address: &Pubkey, data: &[u8]) -> CliResult<AddressLookupTableAccount> {
+    let addresses = data
+        .get(LOOKUP_TABLE_META_SIZE..)
+        .filter(|addresses| addresses.len() % 32 == 0)
+        .ok_or_else(|| anyhow!("Invalid address lookup table data for {address}"))?
+        .chunks(32)
+        .map(Pubkey::try_from)
+        .collect::<Result<_, _>>()?;
+
+    Ok(AddressLookupTableAccount {
+        key: *address,
+        addresses,
+    })
+}
+
+/// Maximum amount of time to wait for an airdrop to get confirmed
+const AIRDROP_TIMEOUT_MS: u64 = 30_000;
+/// Maximum amount of time to wait for a sent transaction to get confirmed
+const CONFIRM_TIMEOUT_MS: u32 = 30_000;
+/// Interval between signature status checks
+const POLL_INTERVAL_MS: u64 = 1_000;
+
+pub async fn request_airdrop(pubkey: &Pubkey, lamports: u64) -> CliResult<Signature> {
+    let client = get_client();
+    let signature = client.request_airdrop(pubkey, lamports).await?;
+
+    let confirmed = poll_signature_status(
+        get_signature_status_fn(&client, signature),
+        client.commitment(),
+        AIRDROP_TIMEOUT_MS,
+    )
+    .await?;
+    match confirmed {
+        true => Ok(signature),
+        false => Err(PgError::AirdropNotConfirmed {
+            timeout_ms: AIRDROP_TIMEOUT_MS,
+        }
+        .into()),
+    }
+}
+
+/// Wait until the transaction is confirmed with the commitment from [`get_confirm_commitment`]
+pub async fn confirm_transaction(sig: &Signature, timeout_ms: u32) -> CliResult {
+    let client = get_client();
+    let confirmed = poll_signature_status(
+        get_signature_status_fn(&client, *sig),
+        get_confirm_commitment(&client),
+        timeout_ms as u64,
+    )
+    .await?;
+    match confirmed {
+        true => Ok(()),
+        false => Err(PgError::NotConfirmed {
+            signature: *sig,
+            timeout_ms: timeout_ms as u64,
+        }
+        .into()),
+    }
+}
+
+/// Get the statuses of the signatures in batches, preserving the order of the signatures.
+/// Unknown signatures are returned as `None`.
+pub async fn get_signature_statuses(
+    sigs: &[Signature],
+) -> CliResult<Vec<Option<SignatureStatusesValue>>> {
+    let client = get_client();
+    get_in_chunks(sigs, MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, |chunk| {
+        client.get_signature_statuses(chunk)
+    })
+    .await
+}
+
+/// Wait until all of the transactions are confirmed with the commitment from
+/// [`get_confirm_commitment`].
+///
+/// Errors on the first failed transaction.
+pub async fn confirm_all(sigs: &[Signature], timeout_ms: u32) -> CliResult {
+    poll_signature_statuses(
+        |sigs| async move { get_signature_statuses(&sigs).await },
+        sigs,
+        get_confirm_commitment(&get_client()),
+        timeout_ms as u64,
+    )
+    .await
+}
+
+/// Poll the statuses of the pending signatures until all of them reach the given commitment level
+async fn poll_signature_statuses<F, Fut>(
+    mut get_statuses: F,
+    sigs: &[Signature],
+    commitment: CommitmentLevel,
+    timeout_ms: u64,
+) -> CliResult
+where
+    F: FnMut(Vec<Signature>) -> Fut,
+    Fut: Future<Output = CliResult<Vec<Option<SignatureStatusesValue>>>>,
+{
+    let mut pending = sigs.to_vec();
+    let mut elapsed_ms = 0;
+    loop {
+        let statuses = get_statuses(pending.clone()).await?;
+        let mut still_pending = vec![];
+        for (sig, status) in pending.into_iter().zip(statuses) {
+            let confirmed = match status {
+                Some(status) => is_status_confirmed(&status, commitment)?,
+                None => false,
+            };
+            if !confirmed {
+                still_pending.push(sig);
+            }
+        }
+        pending = still_pending;
+
+        match pending.first() {
+            None => return Ok(()),
+            Some(signature) if elapsed_ms >= timeout_ms => {
+                return Err(PgError::NotConfirmed {
+                    signature: *signature,
+                    timeout_ms,
+                }
+                .into())
+            }
+            _ => {}
+        }
+
+        sleep(POLL_INTERVAL_MS).await;
+        elapsed_ms += POLL_INTERVAL_MS;
+    }
+}
+
+/// Get the commitment to confirm transactions with.
+///
+/// `PgConnection::confirm_commitment` takes precedence over the client commitment when it's set.
+fn get_confirm_commitment(client: &WasmClient) -> CommitmentLevel {
+    resolve_confirm_commitment(
+        PgConnection::confirm_commitment().as_deref(),
+        client.commitment(),
+    )
+    .unwrap_or_else(|e| {
+        PgTerminal::log_wasm(&format!("Warning: {e}, using the client commitment"));
+        client.commitment()
+    })
+}
+
+fn resolve_confirm_commitment(
+    confirm_commitment: Option<&str>,
+    client_commitment: CommitmentLevel,
+) -> CliResult<CommitmentLevel> {
+    match confirm_commitment {
+        Some(commitment) => parse_commitment(commitment),
+        None => Ok(client_commitment),
+    }
+}
+
+/// Maximum memo length that fits in a transaction with a single signer
+const MAX_MEMO_LEN: usize = 566;
+
+/// Build an SPL Memo instruction that can be sent with [`send_and_confirm`].
+///
+/// All of the `signers` must sign the transaction.
+pub fn memo_instruction(text: &str, signers: &[Pubkey]) -> CliResult<Instruction> {
+    if text.len() > MAX_MEMO_LEN {
+        return Err(anyhow!(
+            "Memo is too long: {} bytes, maximum is {MAX_MEMO_LEN} bytes",
+            text.len()
+        ));
+    }
+
+    let signers = signers.iter().collect::<Vec<_>>();
+    Ok(spl_memo::build_memo(text.as_bytes(), &signers))
+}
+
+/// Build, sign, send and confirm a legacy transaction.
+///
+/// The transaction is confirmed with the commitment from [`get_confirm_commitment`]. Errors with
+/// [`PgError::TransactionExpired`] if the transaction fails after its blockhash has expired.
+///
+/// If the cached blockhash is rejected by the endpoint, the transaction is signed and sent once
+/// more with a newly fetched blockhash.
+pub async fn send_and_confirm(
+    payer: &dyn signer::PgSigner,
+    ixs: &[Instruction],
+    extra_signers: &[&dyn signer::PgSigner],
+) -> CliResult<Signature> {
+    let mut ixs = ixs.to_vec();
+    apply_priority_fee(&mut ixs);
+
+    let client = get_client();
+    let mut retried = false;
+    loop {
+        let (blockhash, last_valid_block_height) = get_cached_blockhash_with_expiry().await?;
+        let tx = sign_transaction(payer, &ixs, extra_signers, blockhash).await?;
+
+        let signature = tx.signatures[0];
+        let result = match client.send_transaction(&tx).await {
+            Ok(_) => confirm_transaction(&signature, CONFIRM_TIMEOUT_MS).await,
+            Err(e) if !retried && is_stale_blockhash_error(&e.to_string()) => {
+                invalidate_blockhash_cache();
+                retried = true;
+                continue;
+            }
+            Err(e) => Err(e.into()),
+        };
+        return match result {
+            Ok(()) => Ok(signature),
+            // The transaction was processed, its blockhash is irrelevant
+            Err(e) if matches!(e.downcast_ref(), Some(PgError::TransactionFailed(_))) => Err(e),
+            Err(e) => {
+                let expired = client
+                    .get_block_height()
+                    .await
+                    .ok()
+                    .and_then(|block_height| {
+                        expired_error(&signature, block_height, last_valid_block_height)
+                    });
+                Err(expired.map(Into::into).unwrap_or(e))
+            }
+        };
+    }
+}
+
+/// Whether the send error means that the blockhash of the transaction can't be used.
+///
+/// A cached blockhash is either unknown to the current node or it results in a transaction that
+/// is identical to an already processed one.
+fn is_stale_blockhash_error(message: &str) -> bool {
+    message.contains("Blockhash not found") || message.contains("already been processed")
+}
+
+/// Get the expiry error if the block height has passed the last valid block height of the
+/// transaction's blockhash
+fn expired_error(
+    signature: &Signature,
+    block_height: u64,
+    last_valid_block_height: u64,
+) -> Option<PgError> {
+    (block_height > last_valid_block_height).then_some(PgError::TransactionExpired(*signature))
+}
+
+/// Build and sign a legacy transaction, the payer is the first signer
+async fn sign_transaction(
+    payer: &dyn signer::PgSigner,
+    ixs: &[Instruction],
+    extra_signers: &[&dyn signer::PgSigner],
+    blockhash: Hash,
+) -> CliResult<Transaction> {
+    let message = Message::new_with_blockhash(ixs, Some(&payer.pubkey()), &blockhash);
+    let message_data = message.serialize();
+
+    let mut signatures = vec![];
+    let signer_pubkeys = &message.account_keys[..message.header.num_required_signatures as usize];
+    for pubkey in signer_pubkeys {
+        let signer = std::iter::once(payer)
+            .chain(extra_signers.iter().copied())
+            .find(|signer| signer.pubkey() == *pubkey)
+            .ok_or_else(|| anyhow!("Missing signer {pubkey}"))?;
+        signatures.push(signer.sign_transaction_message(&message_data).await?);
+    }
+
+    Ok(Transaction {
+        signatures,
+        message,
+    })
+}
+
+/// Create a rent exempt account that is owned by the given program
+pub async fn create_account(
+    payer: &dyn signer::PgSigner,
+    new_account: &dyn signer::PgSigner,
+    space: u64,
+    owner: &Pubkey,
+) -> CliResult<Signature> {
+    let client = get_client();
+    let rent = client
+        .get_minimum_balance_for_rent_exemption(space as usize)
+        .await?;
+    let ixs = create_account_instructions(
+        &payer.pubkey(),
+        &new_account.pubkey(),
+        rent,
+        space,
+        owner,
+        PgConnection::priority_fee(),
+    );
+    let message =
+        Message::new_with_blockhash(&ixs, Some(&payer.pubkey()), &get_cached_blockhash().await?);
+    let fee = estimate_fee(&message).await?;
+    let balance = client.get_balance(&payer.pubkey()).await?;
+    check_create_account_balance(balance, rent, fee, space)?;
+
+    send_and_confirm(payer, &ixs, &[new_account]).await
+}
+
+/// Build the instructions of [`create_account`] along with the priority fee
+fn create_account_instructions(
+    payer: &Pubkey,
+    new_account: &Pubkey,
+    rent: u64,
+    space: u64,
+    owner: &Pubkey,
+    micro_lamports: u64,
+) -> Vec<Instruction> {
+    let mut ixs = vec![system_instruction::create_account(
+        payer,
+        new_account,
+        rent,
+        space,
+        owner,
+    )];
+    attach_priority_fee(&mut ixs, micro_lamports);
+    ixs
+}
+
+/// Check whether the payer can pay for both the rent exemption and the transaction fee
+fn check_create_account_balance(balance: u64, rent: u64, fee: u64, space: u64) -> CliResult {
+    if balance < rent + fee {
+        return Err(anyhow!(
+            "Insufficient balance to create the account: rent exemption for {space} bytes is \
+            {rent} lamports ({} SOL) and the fee is {fee} lamports, balance is {balance} lamports",
+            lamports_to_sol(rent)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Close the system account by transferring all of its lamports to the destination.
+///
+/// The account must sign because only the account itself can transfer lamports out of a system
+/// account. Program owned accounts can't be closed directly, they need to be closed by their
+/// program's close instruction e.g. with [`build_instruction`].
+pub async fn close_account(
+    payer: &dyn signer::PgSigner,
+    account: &dyn signer::PgSigner,
+    destination: &Pubkey,
+) -> CliResult<Signature> {
+    let client = get_client();
+    let address = account.pubkey();
+    let account_info = client
+        .get_account_with_commitment(&address, client.commitment_config())
+        .await?
+        .ok_or_else(|| anyhow!("Account {address} not found"))?;
+    check_closable(&address, &account_info)?;
+
+    // The fee needs to be left in the account if it's also paying for the transaction
+    let fee = match payer.pubkey() == address {
+        true => {
+            // The priority fee is also paid by the account
+            let mut ixs = vec![system_instruction::transfer(
+                &address,
+                destination,
+                account_info.lamports,
+            )];
+            apply_priority_fee(&mut ixs);
+            let message =
+                Message::new_with_blockhash(&ixs, Some(&address), &get_cached_blockhash().await?);
+            estimate_fee(&message).await?
+        }
+        false => 0,
+    };
+    let lamports = account_info.lamports.checked_sub(fee).ok_or_else(|| {
+        anyhow!(
+            "Insufficient balance to close {address}: {} lamports, fee is {fee} lamports",
+            account_info.lamports
+        )
+    })?;
+
+    let ix = system_instruction::transfer(&address, destination, lamports);
+    send_and_confirm(payer, &[ix], &[account]).await
+}
+
+fn check_closable(address: &Pubkey, account: &Account) -> CliResult {
+    if account.owner != system_program::id() {
+        return Err(anyhow!(
+            "{address} is owned by {} and can't be closed directly, \
+            use the program's close instruction instead",
+            account.owner
+        ));
+    }
+    // The system program can't transfer lamports out of accounts that carry data
+    if !account.data.is_empty() {
+        return Err(anyhow!(
+            "{address} holds {} bytes of data and can't be closed, \
+            only accounts without data can be closed",
+            account.data.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Get the confirmed transaction with its status and logs
+pub async fn get_transaction(
+    sig: &Signature,
+) -> CliResult<EncodedConfirmedTransactionWithStatusMeta> {
+    let client = get_client();
+    // `processed` commitment is not supported for transactions
+    let commitment = match client.commitment() {
+        CommitmentLevel::Finalized => CommitmentLevel::Finalized,
+        _ => CommitmentLevel::Confirmed,
+    };
+
+    client
+        .try_get_transaction_with_config(
+            sig,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(CommitmentConfig { commitment }),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?
+        .ok_or_else(|| anyhow!("Transaction {sig} not found, it might not be confirmed yet"))
+}
+
+/// Get the log messages of the transaction
+pub async fn get_transaction_logs(sig: &Signature) -> CliResult<Vec<String>> {
+    let tx = get_transaction(sig).await?;
+    Ok(tx
+        .transaction
+        .meta
+        .and_then(|meta| meta.log_messages)
+        .unwrap_or_default())
+}
+
+/// Create a function that fetches the status of the given signature
+fn get_signature_status_fn(
+    client: &WasmClient,
+    signature: Signature,
+) -> impl FnMut() -> Pin<Box<dyn Future<Output = CliResult<Option<SignatureStatusesValue>>> + '_>> {
+    move || {
+        Box::pin(async move {
+            let statuses = client.get_signature_statuses(&[signature]).await?;
+            Ok(statuses.into_iter().next().flatten())
+        })
+    }
+}
+
+/// Poll the signature status until it reaches the given commitment level.
+///
+/// Returns `false` if the timeout is reached and errors if the transaction has failed.
+async fn poll_signature_status<F, Fut>(
+    mut get_status: F,
+    commitment: CommitmentLevel,
+    timeout_ms: u64,
+) -> CliResult<bool>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = CliResult<Option<SignatureStatusesValue>>>,
+{
+    let mut elapsed_ms = 0;
+    loop {
+        if let Some(status) = get_status().await? {
+            if is_status_confirmed(&status, commitment)? {
+                return Ok(true);
+            }
+        }
+
+        if elapsed_ms >= timeout_ms {
+            return Ok(false);
+        }
+
+        sleep(POLL_INTERVAL_MS).await;
+        elapsed_ms += POLL_INTERVAL_MS;
+    }
+}
+
+/// Get whether the status has reached the commitment level, errors if the transaction has failed
+fn is_status_confirmed(
+    status: &SignatureStatusesValue,
+    commitment: CommitmentLevel,
+) -> CliResult<bool> {
+    if let Some(err) = &status.err {
+        return Err(PgError::TransactionFailed(err.to_string()).into());
+    }
+
+    Ok(status
+        .confirmation_status
+        .as_ref()
+        .map(|confirmation_status| is_commitment_reached(confirmation_status, commitment))
+        .unwrap_or_default())
+}
+
+/// Wait until the program account exists and is executable
+pub async fn wait_for_deploy(program_id: &Pubkey, timeout_ms: u32) -> CliResult {
+    let client = &get_client();
+    let deployed = poll_program_deployed(
+        move || async move {
+            client
+                .get_account_with_commitment(program_id, client.commitment_config())
+                .await
+        },
+        timeout_ms as u64,
+    )
+    .await?;
+    match deployed {
+        true => Ok(()),
+        false => Err(PgError::NotDeployed(*program_id).into()),
+    }
+}
+
+/// Poll the program account with exponential backoff until it's executable.
+///
+/// Returns `false` if the timeout is reached.
+async fn poll_program_deployed<F, Fut>(mut get_account: F, timeout_ms: u64) -> CliResult<bool>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ClientResult<Option<Account>>>,
+{
+    let mut elapsed_ms = 0;
+    let mut attempt = 0;
+    loop {
+        if let Some(account) = get_account().await? {
+            if account.executable {
+                return Ok(true);
+            }
+        }
+
+        if elapsed_ms >= timeout_ms {
+            return Ok(false);
+        }
+
+        let delay_ms = retry_delay_ms(attempt).min(timeout_ms - elapsed_ms);
+        sleep(delay_ms).await;
+        elapsed_ms += delay_ms;
+        attempt += 1;
+    }
+}
+
+/// Get whether the given confirmation status satisfies the commitment level
+fn is_commitment_reached(
+    confirmation_status: &TransactionConfirmationStatus,
+    commitment: CommitmentLevel,
+) -> bool {
+    match commitment {
+        CommitmentLevel::Finalized => {
+            matches!(
+                confirmation_status,
+                TransactionConfirmationStatus::Finalized
+            )
+        }
+        CommitmentLevel::Confirmed => matches!(
+            confirmation_status,
+            TransactionConfirmationStatus::Finalized | TransactionConfirmationStatus::Confirmed
+        ),
+        _ => true,
+    }
+}
+
+/// Handle of an account subscription.
+///
+/// The subscription is stopped when the handle is dropped.
+pub struct SubscriptionHandle {
+    is_active: Rc<Cell<bool>>,
+    /// Stops the subscription and closes the connection, called at most once
+    stop: RefCell<Option<Box<dyn FnOnce()>>>,
+}
+
+impl SubscriptionHandle {
+    /// Stop the subscription, `on_change` will not be called after this
+    pub fn unsubscribe(&self) {
+        self.is_active.set(false);
+        let stop = self.stop.borrow_mut().take();
+        if let Some(stop) = stop {
+            stop();
+        }
+    }
+
+    /// Whether the subscription is still receiving notifications, `false` after the connection
+    /// is closed
+    pub fn is_active(&self) -> bool {
+        self.is_active.get()
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        self.unsubscribe();
+    }
+}
+
+/// Request id of `accountSubscribe`
+const ACCOUNT_SUBSCRIBE_ID: u64 = 1;
+/// Request id of `accountUnsubscribe`
+const ACCOUNT_UNSUBSCRIBE_ID: u64 = 2;
+
+/// Call `on_change` whenever the account changes, until [`SubscriptionHandle::unsubscribe`] is
+/// called or the handle is dropped.
+///
+/// Changes are received with an `accountSubscribe` websocket subscription to the websocket
+/// endpoint of the configured endpoint. Deleted accounts are reported as an account with 0
+/// lamports. The subscription ends with a warning if the connection is closed by the endpoint.
+pub async fn subscribe_account<F: Fn(Account) + 'static>(
+    address: &Pubkey,
+    on_change: F,
+) -> CliResult<SubscriptionHandle> {
+    let endpoint = websocket_endpoint(&PgConnection::endpoint())?;
+    let connection_error = |e: JsValue| anyhow!("Failed to connect to {endpoint}: {e:?}");
+    let socket = WebSocket::new(&endpoint).map_err(connection_error)?;
+    wait_for_open(&socket).await.map_err(connection_error)?;
+    socket
+        .send_with_str(&account_subscribe_request(address, get_commitment()))
+        .map_err(|e| anyhow!("Failed to subscribe to {address}: {e:?}"))?;
+
+    let is_active = Rc::new(Cell::new(true));
+    let subscription_id = Rc::new(Cell::new(None));
+
+    let on_message = {
+        let subscription_id = Rc::clone(&subscription_id);
+        Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            let text = match event.data().as_string() {
+                Some(text) => text,
+                None => return,
+            };
+            match parse_account_subscription_message(&text) {
+                Ok(Some(AccountSubscriptionMessage::Subscribed(id))) => {
+                    subscription_id.set(Some(id))
+                }
+                Ok(Some(AccountSubscriptionMessage::Notification(account))) => on_change(account),
+                Ok(Some(AccountSubscriptionMessage::Error(e))) => {
+                    PgTerminal::log_wasm(&format!("Warning: account subscription failed: {e}"))
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    PgTerminal::log_wasm(&format!("Warning: invalid account notification: {e}"))
+                }
+            }
+        })
+    };
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+    let on_close = {
+        let is_active = Rc::clone(&is_active);
+        Closure::<dyn FnMut()>::new(move || {
+            if is_active.replace(false) {
+                PgTerminal::log_wasm("Warning: account subscription connection closed");
+            }
+        })
+    };
+    socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+    let stop = move || {
+        socket.set_onmessage(None);
+        socket.set_onclose(None);
+        if let Some(id) = subscription_id.get() {
+            if socket.ready_state() == WebSocket::OPEN {
+                socket.send_with_str(&account_unsubscribe_request(id)).ok();
+            }
+        }
+        socket.close().ok();
+
+        // The handlers are no longer used by the socket
+        drop((on_message, on_close));
+    };
+
+    Ok(SubscriptionHandle {
+        is_active,
+        stop: RefCell::new(Some(Box::new(stop))),
+    })
+}
+
+/// Wait until the websocket connection is open, errors if the connection fails
+async fn wait_for_open(socket: &WebSocket) -> Result<(), JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        socket.set_onopen(Some(&resolve));
+        socket.set_onerror(Some(&reject));
+    });
+    let result = wasm_bindgen_futures::JsFuture::from(promise).await;
+    socket.set_onopen(None);
+    socket.set_onerror(None);
+    result.map(|_| ())
+}
+
+/// Get the websocket endpoint of the RPC endpoint.
+///
+/// Explicit ports are incremented by one, e.g. `http://localhost:8899` becomes
+/// `ws://localhost:8900`, the same as the Solana CLI.
+fn websocket_endpoint(endpoint: &str) -> CliResult<String> {
+    let invalid_endpoint = || anyhow!("Invalid endpoint '{endpoint}'");
+    let (scheme, rest) = endpoint.split_once("://").ok_or_else(invalid_endpoint)?;
+    let scheme = match scheme {
+        "http" => "ws",
+        "https" => "wss",
+        _ => return Err(invalid_endpoint()),
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let authority = match authority
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+    {
+        Some((host, port)) => format!("{host}:{}", port.saturating_add(1)),
+        None => authority.to_owned(),
+    };
+
+    Ok(format!("{scheme}://{authority}{path}"))
+}
+
+fn account_subscribe_request(address: &Pubkey, commitment: CommitmentLevel) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": ACCOUNT_SUBSCRIBE_ID,
+        "method": "accountSubscribe",
+        "params": [address.to_string(), { "encoding": "base64", "commitment": commitment }],
+    })
+    .to_string()
+}
+
+fn account_unsubscribe_request(subscription_id: u64) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": ACCOUNT_UNSUBSCRIBE_ID,
+        "method": "accountUnsubscribe",
+        "params": [subscription_id],
+    })
+    .to_string()
+}
+
+/// Websocket message of an account subscription
+#[derive(Debug, PartialEq)]
+enum AccountSubscriptionMessage {
+    /// Response of `accountSubscribe` with the subscription id
+    Subscribed(u64),
+    Notification(Account),
+    Error(String),
+}
+
+/// Parse the websocket message, returns `None` for messages that are not related to the
+/// subscription, e.g. the response of `accountUnsubscribe`
+fn parse_account_subscription_message(text: &str) -> CliResult<Option<AccountSubscriptionMessage>> {
+    let message: serde_json::Value = serde_json::from_str(text)?;
+    if let Some(error) = message.get("error") {
+        let reason = error
+            .get("message")
+            .and_then(serde_json::Value::as_str)
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| error.to_string());
+        return Ok(Some(AccountSubscriptionMessage::Error(reason)));
+    }
+
+    if message.get("method").and_then(serde_json::Value::as_str) == Some("accountNotification") {
+        let ui_account: UiAccount =
+            serde_json::from_value(message["params"]["result"]["value"].clone())?;
+        let account = ui_account
+            .decode()
+            .ok_or_else(|| anyhow!("Failed to decode the account data"))?;
+        return Ok(Some(AccountSubscriptionMessage::Notification(account)));
+    }
+
+    match message.get("id").and_then(serde_json::Value::as_u64) {
+        Some(ACCOUNT_SUBSCRIBE_ID) => message
+            .get("result")
+            .and_then(serde_json::Value::as_u64)
+            .map(|id| Some(AccountSubscriptionMessage::Subscribed(id)))
+            .ok_or_else(|| anyhow!("Invalid subscription response: {message}")),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keypair_from_bytes() {
+        let keypair = Keypair::new();
+        assert_eq!(
+            keypair_from_bytes(&keypair.to_bytes()).unwrap().to_bytes(),
+            keypair.to_bytes()
+        );
+
+        let err = keypair_from_bytes(&[0; 32]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid wallet keypair bytes: expected 64 bytes, got 32"
+        );
+    }
+
+    #[test]
+    fn test_is_commitment_reached() {
+        use TransactionConfirmationStatus::*;
+
+        assert!(is_commitment_reached(
+            &Processed,
+            CommitmentLevel::Processed
+        ));
+        assert!(!is_commitment_reached(
+            &Processed,
+            CommitmentLevel::Confirmed
+        ));
+        assert!(is_commitment_reached(
+            &Confirmed,
+            CommitmentLevel::Confirmed
+        ));
+        assert!(!is_commitment_reached(
+            &Confirmed,
+            CommitmentLevel::Finalized
+        ));
+        assert!(is_commitment_reached(
+            &Finalized,
+            CommitmentLevel::Confirmed
+        ));
+        assert!(is_commitment_reached(
+            &Finalized,
+            CommitmentLevel::Finalized
+        ));
+    }
+
+    #[test]
+    fn test_poll_signature_status() {
+        let status = |confirmation_status| SignatureStatusesValue {
+            slot: 0,
+            confirmations: None,
+            err: None,
+            confirmation_status: Some(confirmation_status),
+        };
+
+        // Confirmed status short-circuits the loop
+        let mut calls = 0;
+        let confirmed = futures::executor::block_on(poll_signature_status(
+            || {
+                calls += 1;
+                async { Ok(Some(status(TransactionConfirmationStatus::Confirmed))) }
+            },
+            CommitmentLevel::Confirmed,
+            10_000,
+        ))
+        .unwrap();
+        assert!(confirmed);
+        assert_eq!(calls, 1);
+
+        // Insufficient commitment times out
+        let confirmed = futures::executor::block_on(poll_signature_status(
+            || async { Ok(Some(status(TransactionConfirmationStatus::Processed))) },
+            CommitmentLevel::Confirmed,
+            0,
+        ))
+        .unwrap();
+        assert!(!confirmed);
+    }
+
+    #[test]
+    fn test_parse_idl_cached() {
+        let idl_string =
+            |name: &str| format!(r#"{{"version":"0.1.0","name":"{name}","instructions":[]}}"#);
+
+        assert_eq!(parse_idl_cached(idl_string("first")).unwrap().name, "first");
+        assert_eq!(parse_idl_cached(idl_string("first")).unwrap().name, "first");
+
+        // Changing the IDL string should rebuild the cache
+        assert_eq!(
+            parse_idl_cached(idl_string("second")).unwrap().name,
+            "second"
+        );
+        IDL_CACHE.with(|cache| {
+            assert_eq!(cache.borrow().as_ref().unwrap().0, idl_string("second"));
+        });
+
+        // Invalid IDLs should not replace the cache
+        assert!(parse_idl_cached(idl_string("third")[..20].to_owned()).is_err());
+        IDL_CACHE.with(|cache| {
+            assert_eq!(cache.borrow().as_ref().unwrap().0, idl_string("second"));
+        });
+    }
+
+    #[test]
+    fn test_parse_idl() {
+        let idl_string = r#"{"version":"0.1.0","name":"test","instructions":[]}"#;
+        assert_eq!(parse_idl(idl_string).unwrap().name, "test");
+
+        let err = parse_idl(&idl_string[..idl_string.len() - 5]).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to parse IDL: "));
+    }
+
+    #[test]
+    fn test_parse_program_id() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            parse_program_id(&program_id.to_string()).unwrap(),
+            program_id
+        );
+
+        let err = parse_program_id("notbase58!!!").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Invalid program id 'notbase58!!!': "));
+    }
+
+    #[test]
+    fn test_parse_commitment() {
+        assert_eq!(
+            parse_commitment("processed").unwrap(),
+            CommitmentLevel::Processed
+        );
+        assert_eq!(
+            parse_commitment("confirmed").unwrap(),
+            CommitmentLevel::Confirmed
+        );
+        assert_eq!(
+            parse_commitment("finalized").unwrap(),
+            CommitmentLevel::Finalized
+        );
+        assert!(parse_commitment("finalised").is_err());
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        assert_eq!(
+            parse_headers(r#"{"x-api-key":"secret"}"#).unwrap(),
+            vec![("x-api-key".to_owned(), "secret".to_owned())]
+        );
+        assert!(parse_headers("{}").unwrap().is_empty());
+
+        let err = parse_headers(r#"{"x-api-key":"#).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid custom headers JSON: "));
+        assert!(parse_headers(r#"{"x-retries":3}"#).is_err());
+    }
+
+    #[test]
+    fn test_mark_warning_shown() {
+        assert!(mark_warning_shown("unknown commitment 'a'"));
+        assert!(!mark_warning_shown("unknown commitment 'a'"));
+        assert!(mark_warning_shown("unknown commitment 'b'"));
+    }
+
+    fn rate_limit_error() -> ClientError {
+        serde_json::from_value(serde_json::json!({
+            "id": 0,
+            "jsonrpc": "2.0",
+            "error": { "code": 429, "message": "Too many requests" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_with_retry() {
+        // Fails once with a rate limit error, then succeeds
+        let mut attempts = 0;
+        let result = futures::executor::block_on(with_retry_count(3, || {
+            attempts += 1;
+            let result = match attempts {
+                1 => Err(rate_limit_error()),
+                _ => Ok(attempts),
+            };
+            async move { result }
+        }));
+        assert_eq!(result.unwrap(), 2);
+
+        // Logic errors are not retried
+        let mut attempts = 0;
+        let result = futures::executor::block_on(with_retry_count(3, || {
+            attempts += 1;
+            async { Err::<(), _>(ClientError::new("Invalid param")) }
+        }));
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_retry_delay_ms() {
+        assert_eq!(retry_delay_ms(0), 500);
+        assert_eq!(retry_delay_ms(1), 1_000);
+        assert_eq!(retry_delay_ms(10), RETRY_MAX_DELAY_MS);
+    }
+
+    #[test]
+    fn test_lamports_to_sol() {
+        assert_eq!(lamports_to_sol(1_500_000_000), 1.5);
+        assert_eq!(lamports_to_sol(1), 0.000000001);
+    }
+
+    #[test]
+    fn test_simulation_error() {
+        let logs = vec![
+            "Program log: Instruction: Initialize".to_owned(),
+            "Program log: Error: invalid amount".to_owned(),
+        ];
+        let err = simulation_error(&TransactionError::AccountNotFound, &logs).to_string();
+        assert!(err.starts_with("Simulation failed: "));
+        assert!(err.contains(&logs[0]));
+        assert!(err.contains(&logs[1]));
+    }
+
+    #[test]
+    fn test_keypair_from_mnemonic() {
+        use solana_sdk::signer::Signer;
+
+        const PHRASE: &str =
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+        assert_eq!(
+            keypair_from_mnemonic(PHRASE, "")
+                .unwrap()
+                .pubkey()
+                .to_string(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+        assert_eq!(
+            keypair_from_mnemonic_with_path(PHRASE, "", "m/44'/501'/1'/0'")
+                .unwrap()
+                .pubkey()
+                .to_string(),
+            "Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb"
+        );
+
+        let err = keypair_from_mnemonic("abandon abandon", "").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid mnemonic");
+    }
+
+    #[test]
+    fn test_with_priority_fee() {
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+
+        let mut ixs = vec![ix.clone()];
+        with_priority_fee(&mut ixs, 1_000, None);
+        assert_eq!(
+            ixs,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                ix.clone()
+            ]
+        );
+
+        let mut ixs = vec![ix.clone()];
+        with_priority_fee(&mut ixs, 1_000, Some(200_000));
+        assert_eq!(
+            ixs,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                ix
+            ]
+        );
+    }
+
+    #[test]
+    fn test_attach_priority_fee() {
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+
+        let mut ixs = vec![ix.clone()];
+        attach_priority_fee(&mut ixs, 0);
+        assert_eq!(ixs, vec![ix.clone()]);
+
+        attach_priority_fee(&mut ixs, 1_000);
+        assert_eq!(
+            ixs,
+            vec![
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                ix.clone()
+            ]
+        );
+
+        // Compute budget instructions of the caller are kept
+        let mut ixs = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ix,
+        ];
+        let expected = ixs.clone();
+        attach_priority_fee(&mut ixs, 1_000);
+        assert_eq!(ixs, expected);
+    }
+
+    #[test]
+    fn test_parse_lookup_table() {
+        let address = Pubkey::new_unique();
+        let addresses = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let mut data = vec![0; LOOKUP_TABLE_META_SIZE];
+        for address in &addresses {
+            data.extend_from_slice(address.as_ref());
+        }
+        let lookup_table = parse_lookup_table(&address, &data).unwrap();
+        assert_eq!(lookup_table.key, address);
+        assert_eq!(lookup_table.addresses, addresses);
+
+        assert!(parse_lookup_table(&address, &data[..LOOKUP_TABLE_META_SIZE - 1]).is_err());
+        assert!(parse_lookup_table(&address, &data[..data.len() - 1]).is_err());
+    }
+
+    fn test_idl() -> Idl {
+        serde_json::from_value(serde_json::json!({
+            "version": "0.1.0",
+            "name": "test",
+            "instructions": [{
+                "name": "setData",
+                "accounts": [
+                    { "name": "data", "isMut": true, "isSigner": false },
+                    { "name": "authority", "isMut": false, "isSigner": true }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "label", "type": "string" },
+                    { "name": "owner", "type": { "option": "publicKey" } }
+                ]
+            }],
+            "metadata": { "address": "11111111111111111111111111111111" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("initialize"), "initialize");
+        assert_eq!(to_snake_case("setData"), "set_data");
+        assert_eq!(to_snake_case("initV2Pool"), "init_v2_pool");
+        assert_eq!(to_snake_case("getURLPath"), "get_url_path");
+    }
+
+    #[test]
+    fn test_build_instruction() {
+        let idl = test_idl();
+        let data = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let accounts = [
+            ("authority".to_owned(), authority),
+            ("data".to_owned(), data),
+        ];
+
+        let ix = build_instruction(
+            &idl,
+            "setData",
+            serde_json::json!({ "amount": "5", "label": "hi" }),
+            &accounts,
+        )
+        .unwrap();
+        assert_eq!(ix.program_id, Pubkey::default());
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(data, false),
+                AccountMeta::new_readonly(authority, true)
+            ]
+        );
+
+        let mut expected_data = instruction_discriminator("setData").to_vec();
+        expected_data.extend(5u64.to_le_bytes());
+        expected_data.extend(2u32.to_le_bytes());
+        expected_data.extend(b"hi");
+        expected_data.push(0);
+        assert_eq!(ix.data, expected_data);
+
+        // Unknown argument
+        assert!(build_instruction(
+            &idl,
+            "setData",
+            serde_json::json!({ "amount": 5, "label": "hi", "extra": 1 }),
+            &accounts,
+        )
+        .is_err());
+
+        // Missing account
+        let err = build_instruction(
+            &idl,
+            "setData",
+            serde_json::json!({ "amount": 5, "label": "hi" }),
+            &accounts[..1],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing account 'data' for instruction 'setData'"
+        );
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "version": "0.1.0",
+            "name": "test",
+            "instructions": [],
+            "events": [{
+                "name": "Transferred",
+                "fields": [
+                    { "name": "amount", "type": "u64", "index": false },
+                    { "name": "to", "type": "publicKey", "index": false }
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let logs = [
+            "Program 11111111111111111111111111111111 invoke [1]",
+            "Program log: Instruction: Transfer",
+            "Program data: FYTvQJLvpkToAwAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+            "Program data: AAAAAAAAAAA=",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            parse_events(&idl, &logs),
+            vec![(
+                "Transferred".to_owned(),
+                serde_json::json!({
+                    "amount": "1000",
+                    "to": "11111111111111111111111111111111"
+                })
+            )]
+        );
+    }
+
+    #[test]
+    fn test_explain_error() {
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "version": "0.1.0",
+            "name": "test",
+            "instructions": [],
+            "errors": [{ "code": 6000, "name": "InvalidAmount", "msg": "Amount must be positive" }]
+        }))
+        .unwrap();
+        let client_error = |code: &str| {
+            ClientError::new(&format!(
+                "Transaction simulation failed: Error processing Instruction 0: custom program error: {code}"
+            ))
+        };
+
+        assert_eq!(
+            explain_error(&idl, &client_error("0x1770")),
+            "Amount must be positive"
+        );
+        assert_eq!(
+            explain_error(&idl, &client_error("0x7d3")),
+            ErrorCode::ConstraintRaw.to_string()
+        );
+        for error_code in [
+            ErrorCode::ConstraintTokenOwner,
+            ErrorCode::AccountSysvarMismatch,
+        ] {
+            let hex = format!("0x{:x}", u32::from(error_code));
+            assert_eq!(
+                explain_error(&idl, &client_error(&hex)),
+                error_code.to_string()
+            );
+        }
+        assert_eq!(
+            explain_error(&idl, &client_error("0x2a")),
+            "custom program error: 0x2a"
+        );
+    }
+
+    #[test]
+    fn test_cluster_from_str() {
+        assert_eq!("l".parse::<Cluster>().unwrap(), Cluster::Localnet);
+        assert_eq!("localnet".parse::<Cluster>().unwrap(), Cluster::Localnet);
+        assert_eq!("d".parse::<Cluster>().unwrap(), Cluster::Devnet);
+        assert_eq!("devnet".parse::<Cluster>().unwrap(), Cluster::Devnet);
+        assert_eq!("t".parse::<Cluster>().unwrap(), Cluster::Testnet);
+        assert_eq!("testnet".parse::<Cluster>().unwrap(), Cluster::Testnet);
+        assert_eq!("m".parse::<Cluster>().unwrap(), Cluster::MainnetBeta);
+        assert_eq!(
+            "mainnet-beta".parse::<Cluster>().unwrap(),
+            Cluster::MainnetBeta
+        );
+
+        let url = "https://rpc.example.com";
+        let cluster = url.parse::<Cluster>().unwrap();
+        assert_eq!(cluster, Cluster::Custom(url.to_owned()));
+        assert_eq!(cluster.endpoint(), url);
+
+        assert!("devnett".parse::<Cluster>().is_err());
+    }
+
+    #[test]
+    fn test_get_in_chunks() {
+        let items = (0..150).collect::<Vec<u32>>();
+        let mut chunk_lens = vec![];
+        let results =
+            futures::executor::block_on(get_in_chunks(&items, MAX_MULTIPLE_ACCOUNTS, |chunk| {
+                chunk_lens.push(chunk.len());
+                let results = chunk.iter().map(|i| i * 2).collect::<Vec<_>>();
+                async { Ok(results) }
+            }))
+            .unwrap();
+
+        assert_eq!(chunk_lens, vec![100, 50]);
+        assert_eq!(results, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+
+        // Signature statuses are requested in larger chunks
+        let sigs = (0..300)
+            .map(|i| Signature::new(&[i as u8; 64]))
+            .collect::<Vec<_>>();
+        let mut chunk_lens = vec![];
+        let results = futures::executor::block_on(get_in_chunks(
+            &sigs,
+            MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
+            |chunk| {
+                chunk_lens.push(chunk.len());
+                let results = chunk.to_vec();
+                async { Ok(results) }
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(chunk_lens, vec![256, 44]);
+        assert_eq!(results, sigs);
+    }
+
+    #[test]
+    fn test_find_pda() {
+        let program_id = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
+        let rent = Pubkey::from_str("SysvarRent111111111111111111111111111111111").unwrap();
+
+        let (pda, bump) = find_pda(
+            &program_id,
+            &[Seed::Str("vault".to_owned()), Seed::Pubkey(rent)],
+        );
+        assert_eq!(
+            pda.to_string(),
+            "AiVKqvUu9LsBWBTb9TNe6FLHYqRJbJFqh7CnXGrTc6oa"
+        );
+        assert_eq!(bump, 255);
+
+        let (pda, _) = find_pda(
+            &program_id,
+            &[
+                Seed::Bytes(b"vault".to_vec()),
+                Seed::Pubkey(rent),
+                Seed::U64(7),
+            ],
+        );
+        assert_eq!(
+            pda.to_string(),
+            "2zmNPStSbARxwFv8jmxVu427on8qwwk7UL4ot89qz5w3"
+        );
+    }
+
+    #[test]
+    fn test_build_instruction_resolves_pda() {
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "version": "0.1.0",
+            "name": "test",
+            "instructions": [{
+                "name": "deposit",
+                "accounts": [
+                    { "name": "authority", "isMut": false, "isSigner": true },
+                    {
+                        "name": "vault",
+                        "isMut": true,
+                        "isSigner": false,
+                        "pda": {
+                            "seeds": [
+                                { "kind": "const", "type": "string", "value": "vault" },
+                                { "kind": "account", "type": "publicKey", "path": "authority" },
+                                { "kind": "arg", "type": "u64", "path": "id" }
+                            ]
+                        }
+                    }
+                ],
+                "args": [{ "name": "id", "type": "u64" }]
+            }],
+            "metadata": { "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS" }
+        }))
+        .unwrap();
+        let authority = Pubkey::from_str("SysvarRent111111111111111111111111111111111").unwrap();
+
+        let ix = build_instruction(
+            &idl,
+            "deposit",
+            serde_json::json!({ "id": "7" }),
+            &[("authority".to_owned(), authority)],
+        )
+        .unwrap();
+        assert_eq!(
+            ix.accounts[1].pubkey.to_string(),
+            "2zmNPStSbARxwFv8jmxVu427on8qwwk7UL4ot89qz5w3"
+        );
+
+        assert!(build_instruction(&idl, "deposit", serde_json::json!({ "id": "7" }), &[]).is_err());
+    }
+
+    #[test]
+    fn test_get_cached_blockhash_with_clock() {
+        let now = std::cell::Cell::new(0.0);
+        let fetch_count = std::cell::Cell::new(0);
+        let endpoint = std::cell::RefCell::new("https://api.devnet.solana.com");
+        let get_blockhash = || {
+            futures::executor::block_on(get_cached_blockhash_with_clock(
+                *endpoint.borrow(),
+                || now.get(),
+                || {
+                    fetch_count.set(fetch_count.get() + 1);
+                    async { Ok((Hash::new_unique(), 100)) }
+                },
+            ))
+            .unwrap()
+        };
+
+        let (hash, last_valid_block_height) = get_blockhash();
+        assert_eq!(last_valid_block_height, 100);
+        now.set(BLOCKHASH_TTL_MS - 1.0);
+        assert_eq!(get_blockhash(), (hash, last_valid_block_height));
+        assert_eq!(fetch_count.get(), 1);
+
+        now.set(BLOCKHASH_TTL_MS);
+        let (hash, _) = get_blockhash();
+        assert_eq!(fetch_count.get(), 2);
+
+        // Blockhashes of other endpoints are not reused
+        *endpoint.borrow_mut() = "https://api.testnet.solana.com";
+        let (testnet_hash, _) = get_blockhash();
+        assert_ne!(testnet_hash, hash);
+        assert_eq!(fetch_count.get(), 3);
+
+        // Invalidated blockhashes are not reused
+        invalidate_blockhash_cache();
+        assert_ne!(get_blockhash().0, testnet_hash);
+        assert_eq!(fetch_count.get(), 4);
+    }
+
+    #[test]
+    fn test_expired_error() {
+        let signature = Signature::default();
+        assert!(expired_error(&signature, 100, 100).is_none());
+
+        let err = expired_error(&signature, 101, 100).unwrap();
+        assert_eq!(err.code(), "TRANSACTION_EXPIRED");
+        assert_eq!(
+            err.to_string(),
+            format!("Transaction {signature} expired, please retry")
+        );
+    }
+
+    #[test]
+    fn test_is_stale_blockhash_error() {
+        assert!(is_stale_blockhash_error(
+            "Transaction simulation failed: Blockhash not found"
+        ));
+        assert!(is_stale_blockhash_error(
+            "Transaction simulation failed: This transaction has already been processed"
+        ));
+        assert!(!is_stale_blockhash_error(
+            "Transaction simulation failed: Attempt to debit an account but found no record of a prior credit."
+        ));
+    }
+
+    #[test]
+    fn test_subscription_handle_drop() {
+        let is_active = Rc::new(Cell::new(true));
+        let stop_count = Rc::new(Cell::new(0));
+        let handle = SubscriptionHandle {
+            is_active: Rc::clone(&is_active),
+            stop: RefCell::new(Some(Box::new({
+                let stop_count = Rc::clone(&stop_count);
+                move || stop_count.set(stop_count.get() + 1)
+            }))),
+        };
+        assert!(handle.is_active());
+
+        // Dropping an already stopped subscription doesn't stop it again
+        handle.unsubscribe();
+        drop(handle);
+        assert!(!is_active.get());
+        assert_eq!(stop_count.get(), 1);
+    }
+
+    #[test]
+    fn test_websocket_endpoint() {
+        assert_eq!(
+            websocket_endpoint("https://api.devnet.solana.com").unwrap(),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(
+            websocket_endpoint("http://localhost:8899").unwrap(),
+            "ws://localhost:8900"
+        );
+        assert_eq!(
+            websocket_endpoint("https://rpc.example.com:443/v1/key").unwrap(),
+            "wss://rpc.example.com:444/v1/key"
+        );
+        assert!(websocket_endpoint("localhost:8899").is_err());
+    }
+
+    #[test]
+    fn test_account_subscription_requests() {
+        let address = Pubkey::new_unique();
+        let request: serde_json::Value = serde_json::from_str(&account_subscribe_request(
+            &address,
+            CommitmentLevel::Confirmed,
+        ))
+        .unwrap();
+        assert_eq!(request["method"], "accountSubscribe");
+        assert_eq!(request["id"], ACCOUNT_SUBSCRIBE_ID);
+        assert_eq!(
+            request["params"],
+            serde_json::json!([address.to_string(), { "encoding": "base64", "commitment": "confirmed" }])
+        );
+
+        let request: serde_json::Value =
+            serde_json::from_str(&account_unsubscribe_request(5)).unwrap();
+        assert_eq!(request["method"], "accountUnsubscribe");
+        assert_eq!(request["params"], serde_json::json!([5]));
+    }
+
+    #[test]
+    fn test_parse_account_subscription_message() {
+        assert_eq!(
+            parse_account_subscription_message(r#"{"jsonrpc":"2.0","result":23784,"id":1}"#)
+                .unwrap(),
+            Some(AccountSubscriptionMessage::Subscribed(23784))
+        );
+        // Response of `accountUnsubscribe`
+        assert_eq!(
+            parse_account_subscription_message(r#"{"jsonrpc":"2.0","result":true,"id":2}"#)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            parse_account_subscription_message(
+                r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid param"},"id":1}"#
+            )
+            .unwrap(),
+            Some(AccountSubscriptionMessage::Error(
+                "Invalid param".to_owned()
+            ))
+        );
+
+        let owner = Pubkey::new_unique();
+        let notification = |lamports: u64, data: &[u8]| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "accountNotification",
+                "params": {
+                    "result": {
+                        "context": { "slot": 5 },
+                        "value": {
+                            "data": [base64::encode(data), "base64"],
+                            "executable": false,
+                            "lamports": lamports,
+                            "owner": owner.to_string(),
+                            "rentEpoch": 0,
+                            "space": data.len()
+                        }
+                    },
+                    "subscription": 23784
+                }
+            })
+            .to_string()
+        };
+        assert_eq!(
+            parse_account_subscription_message(&notification(10, &[1, 2])).unwrap(),
+            Some(AccountSubscriptionMessage::Notification(Account {
+                lamports: 10,
+                data: vec![1, 2],
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            }))
+        );
+        // Deleted account
+        match parse_account_subscription_message(&notification(0, &[])).unwrap() {
+            Some(AccountSubscriptionMessage::Notification(account)) => {
+                assert_eq!(account.lamports, 0)
+            }
+            message => panic!("Unexpected message: {message:?}"),
+        }
+
+        assert!(parse_account_subscription_message("not json").is_err());
+    }
+
+    #[test]
+    fn test_check_program_account() {
+        let program_id = Pubkey::new_unique();
+        let mut account = Account::new(1, 0, &Pubkey::default());
+
+        assert_eq!(
+            check_program_account(&program_id, None)
+                .unwrap_err()
+                .to_string(),
+            format!("{program_id} is not a deployed program")
+        );
+        assert!(check_program_account(&program_id, Some(&account)).is_err());
+
+        account.executable = true;
+        assert!(check_program_account(&program_id, Some(&account)).is_ok());
+    }
+
+    #[test]
+    fn test_keypair_json() {
+        let keypair = Keypair::new();
+        let json = keypair_to_json(&keypair);
+        assert!(json.starts_with('[') && json.ends_with(']'));
+        assert_eq!(json.split(',').count(), 64);
+        assert_eq!(
+            keypair_from_json(&json).unwrap().to_bytes(),
+            keypair.to_bytes()
+        );
+
+        let rest = vec!["0"; 63].join(",");
+        assert!(keypair_from_json("[1,2,3]").is_err());
+        assert!(keypair_from_json(&format!("[\"a\",{rest}]")).is_err());
+        assert!(keypair_from_json(&format!("[256,{rest}]")).is_err());
+    }
+
+    #[test]
+    fn test_discriminator_filter() {
+        let filter = discriminator_filter("Data");
+        assert!(filter.verify().is_ok());
+        match filter {
+            RpcFilterType::Memcmp(memcmp) => {
+                assert_eq!(memcmp.offset, 0);
+                assert_eq!(
+                    memcmp.bytes().unwrap().as_slice(),
+                    account_discriminator("Data")
+                );
+            }
+            _ => panic!("Expected a memcmp filter"),
+        }
+    }
+
+    #[test]
+    fn test_poll_program_deployed() {
+        let mut program_account = Account::new(1, 0, &Pubkey::default());
+        program_account.executable = true;
+
+        // Executable account short-circuits the loop
+        let mut calls = 0;
+        let deployed = futures::executor::block_on(poll_program_deployed(
+            || {
+                calls += 1;
+                let account = program_account.clone();
+                async { Ok(Some(account)) }
+            },
+            10_000,
+        ))
+        .unwrap();
+        assert!(deployed);
+        assert_eq!(calls, 1);
+
+        // Missing account times out
+        let deployed =
+            futures::executor::block_on(poll_program_deployed(|| async { Ok(None) }, 0)).unwrap();
+        assert!(!deployed);
+    }
+
+    #[test]
+    fn test_sign_transaction() {
+        let payer = Keypair::new();
+        let other = Keypair::new();
+        let ix = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(other.pubkey(), true),
+            ],
+        );
+
+        let tx = futures::executor::block_on(sign_transaction(
+            &payer,
+            &[ix.clone()],
+            &[&other],
+            Hash::new_unique(),
+        ))
+        .unwrap();
+        assert_eq!(tx.signatures.len(), 2);
+        assert_eq!(
+            tx.message.account_keys[..2],
+            [payer.pubkey(), other.pubkey()]
+        );
+        assert!(tx.verify().is_ok());
+
+        let err =
+            futures::executor::block_on(sign_transaction(&payer, &[ix], &[], Hash::new_unique()))
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Missing signer {}", other.pubkey())
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_account_data() {
+        let data = [0, 1, 2, 254, 255];
+        for encoding in [Encoding::Base58, Encoding::Base64, Encoding::Hex] {
+            for data in [&data[..], &[][..]] {
+                let encoded = encode_account_data(data, encoding);
+                assert_eq!(decode_account_data(&encoded, encoding).unwrap(), data);
+            }
+        }
+
+        assert_eq!(encode_account_data(&data, Encoding::Base58), "12Vzei");
+        assert_eq!(encode_account_data(&data, Encoding::Base64), "AAEC/v8=");
+        assert_eq!(encode_account_data(&data, Encoding::Hex), "000102feff");
+        assert!(decode_account_data("0OIl", Encoding::Base58).is_err());
+        assert!(decode_account_data("zz", Encoding::Hex).is_err());
+    }
+
+    #[test]
+    fn test_discriminators() {
+        assert_eq!(
+            instruction_discriminator("initialize"),
+            [175, 175, 109, 31, 13, 152, 155, 237]
+        );
+        assert_eq!(
+            instruction_discriminator("setData"),
+            instruction_discriminator("set_data")
+        );
+        assert_eq!(
+            account_discriminator("IdlAccount"),
+            [140, 36, 166, 2, 103, 197, 33, 164]
+        );
+        assert_eq!(
+            event_discriminator("Transferred"),
+            [21, 132, 239, 64, 146, 239, 166, 68]
+        );
+
+        let discriminator = account_discriminator("Data");
+        let mut data = discriminator.to_vec();
+        data.extend([1, 2, 3]);
+        assert!(verify_discriminator(&data, &discriminator));
+        assert!(!verify_discriminator(&data[..7], &discriminator));
+        assert!(!verify_discriminator(
+            &data,
+            &account_discriminator("Other")
+        ));
+    }
+
+    #[test]
+    fn test_create_client() {
+        for commitment in [
+            CommitmentLevel::Processed,
+            CommitmentLevel::Confirmed,
+            CommitmentLevel::Finalized,
+        ] {
+            let client = create_client(&Cluster::Localnet.endpoint(), commitment, &[]);
+            assert_eq!(client.commitment(), commitment);
+        }
+    }
+
+    #[test]
+    fn test_check_connection_with() {
+        let endpoint = Cluster::Localnet.endpoint();
+        let slot =
+            futures::executor::block_on(check_connection_with(&endpoint, async { Ok(5) })).unwrap();
+        assert_eq!(slot, 5);
+
+        let err = futures::executor::block_on(check_connection_with(&endpoint, async {
+            Err(ClientError::new("Connection refused"))
+        }))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot reach http://localhost:8899: Connection refused"
+        );
+    }
+
+    #[test]
+    fn test_sign_message() {
+        let keypair = Keypair::new();
+        let message = b"Sign in to Solana Playground: 0x1234";
+        let signature = sign_message(&keypair, message);
+
+        assert!(verify_signature(&keypair.pubkey(), message, &signature));
+        assert!(!verify_signature(
+            &Keypair::new().pubkey(),
+            message,
+            &signature
+        ));
+        assert!(!verify_signature(
+            &keypair.pubkey(),
+            b"Different message",
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_resolve_confirm_commitment() {
+        assert_eq!(
+            resolve_confirm_commitment(None, CommitmentLevel::Processed).unwrap(),
+            CommitmentLevel::Processed
+        );
+        assert_eq!(
+            resolve_confirm_commitment(Some("finalized"), CommitmentLevel::Processed).unwrap(),
+            CommitmentLevel::Finalized
+        );
+        assert!(resolve_confirm_commitment(Some("final"), CommitmentLevel::Processed).is_err());
+    }
+
+    #[test]
+    fn test_check_closable() {
+        let address = Pubkey::new_unique();
+        assert!(check_closable(&address, &Account::new(1, 0, &system_program::id())).is_ok());
+
+        let program_id = Pubkey::new_unique();
+        let err = check_closable(&address, &Account::new(1, 8, &program_id)).unwrap_err();
+        assert!(err.to_string().contains(&format!("owned by {program_id}")));
+
+        let err = check_closable(&address, &Account::new(1, 8, &system_program::id())).unwrap_err();
+        assert!(err.to_string().contains("holds 8 bytes of data"));
+    }
+
+    #[test]
+    fn test_keypair_from_seed_string() {
+        let alice = keypair_from_seed_string("alice");
+        assert_eq!(
+            alice.pubkey().to_string(),
+            "FPP21sbqhr2LPjSnJkw5NBetPubeFG4PsQFBxHj8noTq"
+        );
+        assert_eq!(
+            keypair_from_seed_string("alice").to_bytes(),
+            alice.to_bytes()
+        );
+        assert_eq!(
+            keypair_from_seed_string("bob").pubkey().to_string(),
+            "GwCXyJ8AesaucFMTUSZmiFVMhVngpeBxaBC7mQJiZct3"
+        );
+    }
+
+    #[test]
+    fn test_decode_instruction() {
+        let idl = test_idl();
+        let accounts = [
+            ("data".to_owned(), Pubkey::new_unique()),
+            ("authority".to_owned(), Pubkey::new_unique()),
+        ];
+        let args = serde_json::json!({
+            "amount": "5",
+            "label": "hi",
+            "owner": Pubkey::new_unique().to_string()
+        });
+
+        let ix = build_instruction(&idl, "setData", args.clone(), &accounts).unwrap();
+        let (name, decoded_args) = decode_instruction(&idl, &ix.data).unwrap();
+        assert_eq!(name, "setData");
+        assert_eq!(decoded_args, args);
+
+        let err = decode_instruction(&idl, &[1; 8]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown instruction discriminator: 0101010101010101"
+        );
+        assert!(decode_instruction(&idl, &[1; 4]).is_err());
+    }
+
+    #[test]
+    fn test_memo_instruction() {
+        let signer = Pubkey::new_unique();
+        let ix = memo_instruction("gm", &[signer]).unwrap();
+        assert_eq!(ix.program_id, spl_memo::id());
+        assert_eq!(ix.data, b"gm");
+        assert_eq!(ix.accounts, vec![AccountMeta::new_readonly(signer, true)]);
+
+        assert!(memo_instruction(&"a".repeat(MAX_MEMO_LEN + 1), &[]).is_err());
+    }
+
+    #[test]
+    fn test_create_account_instructions() {
+        let payer = Keypair::new();
+        let new_account = Keypair::new();
+        let owner = Pubkey::new_unique();
+
+        let ixs =
+            create_account_instructions(&payer.pubkey(), &new_account.pubkey(), 1, 8, &owner, 0);
+        assert_eq!(
+            ixs,
+            vec![system_instruction::create_account(
+                &payer.pubkey(),
+                &new_account.pubkey(),
+                1,
+                8,
+                &owner
+            )]
+        );
+
+        let ixs =
+            create_account_instructions(&payer.pubkey(), &new_account.pubkey(), 1, 8, &owner, 1000);
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(ixs[0].program_id, compute_budget::id());
+        assert_eq!(ixs[1].program_id, system_program::id());
+
+        // Both the payer and the new account sign
+        let tx = futures::executor::block_on(sign_transaction(
+            &payer,
+            &ixs,
+            &[&new_account],
+            Hash::new_unique(),
+        ))
+        .unwrap();
+        assert_eq!(tx.signatures.len(), 2);
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_check_create_account_balance() {
+        assert!(check_create_account_balance(1005, 1000, 5, 8).is_ok());
+
+        let err = check_create_account_balance(1000, 1000, 5, 8).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("1000 lamports (0.000001 SOL) and the fee is 5 lamports"));
+    }
+
+    #[test]
+    fn test_transfer_tokens_instructions() {
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let to_wallet = Pubkey::new_unique();
+
+        let ixs = transfer_tokens_instructions(&payer, &mint, &to_wallet, 5, 6, false).unwrap();
+        assert_eq!(ixs.len(), 2);
+        assert_eq!(ixs[0].program_id, spl_associated_token_account::id());
+        assert_eq!(ixs[1].program_id, spl_token::id());
+
+        let ixs = transfer_tokens_instructions(&payer, &mint, &to_wallet, 5, 6, true).unwrap();
+        assert_eq!(ixs.len(), 1);
+        assert_eq!(ixs[0].program_id, spl_token::id());
+        assert_eq!(
+            ixs[0].accounts[2].pubkey,
+            get_associated_token_address(&to_wallet, &mint)
+        );
+    }
+
+    #[test]
+    fn test_block_time_or_err() {
+        assert_eq!(
+            block_time_or_err(5, Some(1_700_000_000)).unwrap(),
+            1_700_000_000
+        );
+
+        let err = block_time_or_err(5, None).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Block time of slot 5 is not available"));
+    }
+
+    #[test]
+    fn test_poll_signature_statuses() {
+        let status = |err| SignatureStatusesValue {
+            slot: 0,
+            confirmations: None,
+            err,
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        };
+        let sigs = [
+            Signature::new(&[1; 64]),
+            Signature::new(&[2; 64]),
+            Signature::new(&[3; 64]),
+        ];
+
+        // All confirmed
+        let mut requested = vec![];
+        futures::executor::block_on(poll_signature_statuses(
+            |sigs| {
+                requested.push(sigs.clone());
+                async move { Ok(sigs.iter().map(|_| Some(status(None))).collect()) }
+            },
+            &sigs,
+            CommitmentLevel::Confirmed,
+            0,
+        ))
+        .unwrap();
+        assert_eq!(requested, vec![sigs.to_vec()]);
+
+        // Partly pending, the first pending signature is reported
+        let err = futures::executor::block_on(poll_signature_statuses(
+            |_| async { Ok(vec![Some(status(None)), None, None]) },
+            &sigs,
+            CommitmentLevel::Confirmed,
+            0,
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(PgError::NotConfirmed { signature, .. }) if *signature == sigs[1]
+        ));
+
+        // Failed
+        let err = futures::executor::block_on(poll_signature_statuses(
+            |_| async {
+                Ok(vec![
+                    Some(status(None)),
+                    None,
+                    Some(status(Some(TransactionError::AccountNotFound))),
+                ])
+            },
+            &sigs,
+            CommitmentLevel::Confirmed,
+            0,
+        ))
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(PgError::TransactionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_idl_data() {
+        let idl_data = vec![0; 2 * IDL_MAX_WRITE_SIZE + 1];
+        let chunk_lens = split_idl_data(&idl_data)
+            .iter()
+            .map(|chunk| chunk.len())
+            .collect::<Vec<_>>();
+        assert_eq!(chunk_lens, vec![IDL_MAX_WRITE_SIZE, IDL_MAX_WRITE_SIZE, 1]);
+
+        assert_eq!(split_idl_data(&idl_data[..IDL_MAX_WRITE_SIZE]).len(), 1);
+        assert!(split_idl_data(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_idl_write_transaction_size() {
+        let authority = Pubkey::new_unique();
+        let ix = idl_write_instruction(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &authority,
+            &[u8::MAX; IDL_MAX_WRITE_SIZE],
+        )
+        .unwrap();
+        let mut ixs = vec![ix];
+        with_priority_fee(&mut ixs, u64::MAX, Some(u32::MAX));
+
+        let message = Message::new_with_blockhash(&ixs, Some(&authority), &Hash::new_unique());
+        // Signature count prefix and the signatures
+        let tx_size =
+            1 + 64 * message.header.num_required_signatures as usize + message.serialize().len();
+        assert!(
+            tx_size <= solana_sdk::packet::PACKET_DATA_SIZE,
+            "{tx_size} bytes"
+        );
+    }
+
+    #[test]
+    fn test_idl_account_data_len() {
+        assert_eq!(idl_account_data_len(1_000).unwrap(), 2_000);
+        assert_eq!(
+            idl_account_data_len(40_000).unwrap(),
+            IDL_MAX_DATA_LEN - IDL_HEADER_SIZE
+        );
+        assert!(idl_account_data_len((IDL_MAX_DATA_LEN - IDL_HEADER_SIZE) as usize + 1).is_err());
+    }
+
+    #[test]
+    fn test_serialize_idl() {
+        let idl = parse_idl(
+            r#"{"version":"0.1.0","name":"test","instructions":[],"metadata":{"address":"x"}}"#,
+        )
+        .unwrap();
+
+        let mut json = String::new();
+        ZlibDecoder::new(&serialize_idl(&idl).unwrap()[..])
+            .read_to_string(&mut json)
+            .unwrap();
+        let on_chain_idl = parse_idl(&json).unwrap();
+        assert_eq!(on_chain_idl.name, "test");
+        assert!(on_chain_idl.metadata.is_none());
+    }
+
+    #[test]
+    fn test_check_funded() {
+        let pubkey = Pubkey::new_unique();
+        assert!(check_funded(&pubkey, 5_000, 5_000).is_ok());
+        assert!(check_funded(&pubkey, 5_001, 5_000).is_ok());
+
+        let err = check_funded(&pubkey, 4_999, 5_000).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Wallet {pubkey} has 4999 lamports, needs at least 5000. Try an airdrop.")
+        );
+    }
+}
@@ -20,7 +20,7 @@ use solana_sdk::{
     message::Message,
     pubkey::Pubkey,
     signature::Signature,
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
 };
 
 use crate::{
@@ -33,13 +33,15 @@ use crate::{
             RpcBlockProductionConfig, RpcContextConfig, RpcEpochConfig, RpcGetVoteAccountsConfig,
             RpcKeyedAccount, RpcLargestAccountsConfig, RpcLeaderScheduleConfig,
             RpcProgramAccountsConfig, RpcSendTransactionConfig, RpcSignaturesForAddressConfig,
-            RpcSupplyConfig, RpcTokenAccountsFilter, RpcTransactionConfig,
+            RpcSimulateTransactionConfig, RpcSupplyConfig, RpcTokenAccountsFilter,
+            RpcTransactionConfig,
         },
         rpc_filter::TokenAccountsFilter,
         rpc_response::{
             RpcAccountBalance, RpcBlockProduction, RpcConfirmedTransactionStatusWithSignature,
             RpcInflationGovernor, RpcInflationRate, RpcInflationReward, RpcLeaderSchedule,
-            RpcPerfSample, RpcSupply, RpcVersionInfo, RpcVoteAccountStatus,
+            RpcPerfSample, RpcSimulateTransactionResult, RpcSupply, RpcVersionInfo,
+            RpcVoteAccountStatus,
         },
     },
     ClientError, ClientRequest, ClientResponse, ClientResult,
@@ -66,6 +68,17 @@ impl WasmClient {
         }
     }
 
+    pub fn new_with_headers(
+        endpoint: &str,
+        commitment_config: CommitmentConfig,
+        headers: &[(String, String)],
+    ) -> ClientResult<Self> {
+        Ok(Self {
+            provider: Provider::new_with_headers(endpoint, headers)?,
+            commitment_config,
+        })
+    }
+
     pub fn commitment(&self) -> CommitmentLevel {
         self.commitment_config.commitment
     }
@@ -117,10 +130,10 @@ impl WasmClient {
         signature: &Signature,
         config: RpcTransactionConfig,
     ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta> {
-        let request = GetTransactionRequest::new_with_config(*signature, config).into();
-        let response = GetTransactionResponse::from(self.send(request).await?);
-
-        match response.into() {
+        match self
+            .try_get_transaction_with_config(signature, config)
+            .await?
+        {
             Some(result) => Ok(result),
             None => Err(ClientError::new(&format!(
                 "Signature {signature} not found."
@@ -128,6 +141,18 @@ impl WasmClient {
         }
     }
 
+    /// Same as `get_transaction_with_config` but returns `None` if the transaction is not found
+    pub async fn try_get_transaction_with_config(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> ClientResult<Option<EncodedConfirmedTransactionWithStatusMeta>> {
+        let request = GetTransactionRequest::new_with_config(*signature, config).into();
+        let response = GetTransactionResponse::from(self.send(request).await?);
+
+        Ok(response.into())
+    }
+
     pub async fn get_account_with_config(
         &self,
         pubkey: &Pubkey,
@@ -273,6 +298,71 @@ impl WasmClient {
         .await
     }
 
+    pub async fn send_versioned_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let request =
+            SendTransactionRequest::new_versioned_with_config(transaction.to_owned(), config)
+                .into();
+        let response = SendTransactionResponse::from(self.send(request).await?);
+
+        let signature: Signature = response.into();
+
+        // Same as `send_transaction_with_config`, the signature must match the first signature
+        if signature != transaction.signatures[0] {
+            Err(ClientError::new(&format!(
+                "RPC node returned mismatched signature {:?}, expected {:?}",
+                signature, transaction.signatures[0]
+            )))
+        } else {
+            Ok(transaction.signatures[0])
+        }
+    }
+
+    pub async fn send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ClientResult<Signature> {
+        self.send_versioned_transaction_with_config(
+            transaction,
+            RpcSendTransactionConfig {
+                preflight_commitment: Some(self.commitment()),
+                encoding: Some(UiTransactionEncoding::Base64),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<RpcSimulateTransactionResult> {
+        let request =
+            SimulateTransactionRequest::new_with_config(transaction.to_owned(), config).into();
+        let response = SimulateTransactionResponse::from(self.send(request).await?);
+
+        Ok(response.value)
+    }
+
+    pub async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> ClientResult<RpcSimulateTransactionResult> {
+        self.simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                commitment: Some(self.commitment_config()),
+                encoding: Some(UiTransactionEncoding::Base64),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
     pub async fn confirm_transaction_with_commitment(
         &self,
         signature: &Signature,
@@ -460,16 +550,20 @@ impl WasmClient {
     }
 
     pub async fn get_block_time(&self, slot: Slot) -> ClientResult<UnixTimestamp> {
-        let request = GetBlockTimeRequest::new(slot).into();
-        let response = GetBlockTimeResponse::from(self.send(request).await?);
-
-        let maybe_ts: Option<UnixTimestamp> = response.into();
-        match maybe_ts {
+        match self.try_get_block_time(slot).await? {
             Some(ts) => Ok(ts),
             None => Err(ClientError::new(&format!("Block Not Found: slot={}", slot))),
         }
     }
 
+    /// Same as `get_block_time` but returns `None` if the block time is not available
+    pub async fn try_get_block_time(&self, slot: Slot) -> ClientResult<Option<UnixTimestamp>> {
+        let request = GetBlockTimeRequest::new(slot).into();
+        let response = GetBlockTimeResponse::from(self.send(request).await?);
+
+        Ok(response.into())
+    }
+
     pub async fn get_block_height_with_commitment(
         &self,
         commitment_config: CommitmentConfig,
@@ -624,11 +718,11 @@ impl WasmClient {
         let request = GetMultipleAccountsRequest::new_with_config(pubkeys.to_vec(), config).into();
         let response = GetMultipleAccountsResponse::from(self.send(request).await?);
 
+        // Keep the missing accounts as `None` in order to preserve the order of the pubkeys
         Ok(response
             .value
             .iter()
-            .filter(|maybe_acc| maybe_acc.is_some())
-            .map(|acc| acc.clone().unwrap().decode())
+            .map(|maybe_acc| maybe_acc.as_ref().and_then(|acc| acc.decode()))
             .collect())
     }
 
@@ -641,6 +735,7 @@ impl WasmClient {
             pubkeys,
             RpcAccountInfoConfig {
                 commitment: Some(commitment_config),
+                encoding: Some(UiAccountEncoding::Base64),
                 ..RpcAccountInfoConfig::default()
             },
         )
@@ -64,6 +64,15 @@ impl ClientError {
             ..Default::default()
         }
     }
+
+    /// HTTP status code or JSON RPC error code of the error
+    pub fn code(&self) -> i32 {
+        self.error.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.error.message
+    }
 }
 
 impl fmt::Display for ClientError {
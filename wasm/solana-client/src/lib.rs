@@ -18,7 +18,8 @@ pub use solana_sdk;
 
 // For root level imports
 pub use {
-    client::WasmClient, error::ClientError, request::ClientRequest, response::ClientResponse,
+    client::WasmClient, error::ClientError, methods::SignatureStatusesValue,
+    request::ClientRequest, response::ClientResponse,
 };
 
 pub type ClientResult<T> = std::result::Result<T, error::ClientError>;
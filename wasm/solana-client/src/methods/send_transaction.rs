@@ -1,14 +1,17 @@
 use std::str::FromStr;
 
 use solana_extra_wasm::transaction_status::UiTransactionEncoding;
-use solana_sdk::{signature::Signature, transaction::Transaction};
+use solana_sdk::{
+    signature::Signature,
+    transaction::{Transaction, VersionedTransaction},
+};
 
 use crate::utils::rpc_config::{serialize_and_encode, RpcSendTransactionConfig};
 use crate::{ClientRequest, ClientResponse};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SendTransactionRequest {
-    transaction: Transaction,
+    transaction: VersionedTransaction,
     #[serde(skip_serializing_if = "Option::is_none")]
     config: Option<RpcSendTransactionConfig>,
 }
@@ -16,11 +19,20 @@ pub struct SendTransactionRequest {
 impl SendTransactionRequest {
     pub fn new(transaction: Transaction) -> Self {
         Self {
-            transaction,
+            transaction: transaction.into(),
             config: None,
         }
     }
     pub fn new_with_config(transaction: Transaction, config: RpcSendTransactionConfig) -> Self {
+        Self {
+            transaction: transaction.into(),
+            config: Some(config),
+        }
+    }
+    pub fn new_versioned_with_config(
+        transaction: VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> Self {
         Self {
             transaction,
             config: Some(config),
@@ -36,7 +48,7 @@ impl Into<serde_json::Value> for SendTransactionRequest {
         };
 
         let serialized_encoded =
-            serialize_and_encode::<Transaction>(&self.transaction, encoding).unwrap();
+            serialize_and_encode::<VersionedTransaction>(&self.transaction, encoding).unwrap();
 
         match self.config {
             Some(config) => serde_json::json!([serialized_encoded, config]),
@@ -1,8 +1,14 @@
-use std::str::FromStr;
+use solana_extra_wasm::transaction_status::UiTransactionEncoding;
+use solana_sdk::transaction::Transaction;
 
-use solana_sdk::{signature::Signature, transaction::Transaction};
-
-use crate::{utils::rpc_config::RpcSimulateTransactionConfig, ClientRequest, ClientResponse};
+use super::Context;
+use crate::{
+    utils::{
+        rpc_config::{serialize_and_encode, RpcSimulateTransactionConfig},
+        rpc_response::RpcSimulateTransactionResult,
+    },
+    ClientRequest, ClientResponse,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulateTransactionRequest {
@@ -28,12 +34,18 @@ impl SimulateTransactionRequest {
 
 impl Into<serde_json::Value> for SimulateTransactionRequest {
     fn into(self) -> serde_json::Value {
-        let transaction_data = bincode::serialize(&self.transaction).unwrap();
-        let encoded_transaction = bs58::encode(&transaction_data).into_string();
+        // RPC defaults to base58 when the encoding is not specified
+        let encoding = match self.config {
+            Some(ref c) => c.encoding.unwrap_or(UiTransactionEncoding::Base58),
+            None => UiTransactionEncoding::Base58,
+        };
+
+        let serialized_encoded =
+            serialize_and_encode::<Transaction>(&self.transaction, encoding).unwrap();
 
         match self.config {
-            Some(config) => serde_json::json!([encoded_transaction, config]),
-            None => serde_json::json!([encoded_transaction]),
+            Some(config) => serde_json::json!([serialized_encoded, config]),
+            None => serde_json::json!([serialized_encoded]),
         }
     }
 }
@@ -48,19 +60,14 @@ impl Into<ClientRequest> for SimulateTransactionRequest {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SimulateTransactionResponse(Signature);
-
-impl Into<Signature> for SimulateTransactionResponse {
-    fn into(self) -> Signature {
-        self.0
-    }
+#[serde(rename_all = "camelCase")]
+pub struct SimulateTransactionResponse {
+    pub context: Context,
+    pub value: RpcSimulateTransactionResult,
 }
 
 impl From<ClientResponse> for SimulateTransactionResponse {
     fn from(response: ClientResponse) -> Self {
-        let signature = response.result.as_str().expect("invalid response");
-        let signature = Signature::from_str(signature).expect("invalid signature");
-
-        SimulateTransactionResponse(signature)
+        serde_json::from_value(response.result).unwrap()
     }
 }
@@ -15,6 +15,24 @@ impl HttpProvider {
             url: url.to_owned(),
         }
     }
+
+    pub fn new_with_headers(url: &str, headers: &[(String, String)]) -> ClientResult<Self> {
+        let mut header_map = reqwest::header::HeaderMap::new();
+        for (name, value) in headers {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| ClientError::new(&format!("Invalid header name: {name}")))?;
+            let value = reqwest::header::HeaderValue::from_str(value).map_err(|_| {
+                ClientError::new(&format!("Invalid header value for {}", name.as_str()))
+            })?;
+            header_map.insert(name, value);
+        }
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            headers: header_map,
+            url: url.to_owned(),
+        })
+    }
 }
 
 impl HttpProvider {
@@ -50,4 +68,8 @@ impl Provider {
     pub fn new(url: &str) -> Self {
         Self::Http(HttpProvider::new(url))
     }
+
+    pub fn new_with_headers(url: &str, headers: &[(String, String)]) -> ClientResult<Self> {
+        Ok(Self::Http(HttpProvider::new_with_headers(url, headers)?))
+    }
 }
@@ -1,7 +1,8 @@
 use std::{collections::HashMap, fmt};
 
 use solana_extra_wasm::{
-    account_decoder::parse_token::UiTokenAmount, transaction_status::TransactionConfirmationStatus,
+    account_decoder::{parse_token::UiTokenAmount, UiAccount},
+    transaction_status::TransactionConfirmationStatus,
 };
 use solana_sdk::{
     clock::{Epoch, Slot, UnixTimestamp},
@@ -384,22 +385,22 @@ pub struct RpcVoteAccountInfo {
 //     pub status: Result<()>,
 // }
 
-// #[derive(Serialize, Deserialize, Clone, Debug)]
-// #[serde(rename_all = "camelCase")]
-// pub struct RpcSimulateTransactionResult {
-//     pub err: Option<TransactionError>,
-//     pub logs: Option<Vec<String>>,
-//     pub accounts: Option<Vec<Option<UiAccount>>>,
-//     pub units_consumed: Option<u64>,
-//     pub return_data: Option<RpcTransactionReturnData>,
-// }
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionResult {
+    pub err: Option<TransactionError>,
+    pub logs: Option<Vec<String>>,
+    pub accounts: Option<Vec<Option<UiAccount>>>,
+    pub units_consumed: Option<u64>,
+    pub return_data: Option<RpcTransactionReturnData>,
+}
 
-// #[derive(Serialize, Deserialize, Clone, Debug)]
-// #[serde(rename_all = "camelCase")]
-// pub struct RpcTransactionReturnData {
-//     pub program_id: String,
-//     pub data: (String, ReturnDataEncoding),
-// }
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionReturnData {
+    pub program_id: String,
+    pub data: (String, ReturnDataEncoding),
+}
 
 // impl From<TransactionReturnData> for RpcTransactionReturnData {
 //     fn from(return_data: TransactionReturnData) -> Self {
@@ -410,11 +411,11 @@ pub struct RpcVoteAccountInfo {
 //     }
 // }
 
-// #[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, Hash, PartialEq)]
-// #[serde(rename_all = "camelCase")]
-// pub enum ReturnDataEncoding {
-//     Base64,
-// }
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReturnDataEncoding {
+    Base64,
+}
 
 // #[derive(Serialize, Deserialize, Clone, Debug)]
 // #[serde(rename_all = "camelCase")]